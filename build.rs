@@ -0,0 +1,32 @@
+use std::env;
+
+// Tells the test suite which levels the compile-time cap (`max_level_*` /
+// `release_max_level_*` features) strips, as `cfg(ulogger_strips = "info")`.
+// Mirrors `static_max_level` in src/lib.rs.
+const LEVELS: [&str; 7] = [
+    "verbose", "debug", "info", "warning", "error", "fatal", "fixed",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!(
+        "cargo:rustc-check-cfg=cfg(ulogger_strips, values(\"verbose\", \"debug\", \"info\", \"warning\", \"error\", \"fatal\"))"
+    );
+
+    // Most restrictive enabled feature of a group, as an index into LEVELS
+    let cap = |prefix: &str| {
+        LEVELS.iter().rposition(|level| {
+            env::var_os(format!("CARGO_FEATURE_{}{}", prefix, level.to_uppercase())).is_some()
+        })
+    };
+    let release = if env::var_os("CARGO_CFG_DEBUG_ASSERTIONS").is_none() {
+        cap("RELEASE_MAX_LEVEL_")
+    } else {
+        None
+    };
+    let cap = release.or_else(|| cap("MAX_LEVEL_")).unwrap_or(0);
+
+    for level in &LEVELS[..cap] {
+        println!("cargo:rustc-cfg=ulogger_strips=\"{}\"", level);
+    }
+}
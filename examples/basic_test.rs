@@ -90,12 +90,17 @@ fn main() {
         log_f64!(3.1415926535)
     );
 
+    // --- Format-string macros ---
+    let host = "localhost";
+    log_info!("connected to {} in {}ms", host, 42);
+    log_warning!("retrying ({}/{})", 1, 3);
+
     log_print!(LogLevel::Fixed, log_str!("Ending application..."));
 
     // --- Show file location ---
     {
         let logger = LOGGER.lock().unwrap();
-        if let Some(path) = &logger.log_file_path {
+        if let Some(path) = logger.log_file_path() {
             println!("Log file written to: {}", path);
         }
     }
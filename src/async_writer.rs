@@ -0,0 +1,172 @@
+use crate::outputs::{OutputsRef, OwnedRecord};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+
+// ---------- Async configuration ----------
+/// What `log_print!` does when the async queue is full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait until the writer thread makes room.
+    Block,
+    /// Discard the record being logged.
+    DropNewest,
+    /// Discard the oldest queued record to make room.
+    DropOldest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsyncConfig {
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for AsyncConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+}
+
+// ---------- Bounded queue ----------
+enum Message {
+    Record(OwnedRecord),
+    Flush(mpsc::Sender<()>),
+}
+
+struct QueueState {
+    items: VecDeque<Message>,
+    closed: bool,
+}
+
+struct Queue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+}
+
+impl Queue {
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------- Writer thread ----------
+pub(crate) struct AsyncWriter {
+    queue: Arc<Queue>,
+    config: AsyncConfig,
+    dropped: u64,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+impl AsyncWriter {
+    pub(crate) fn start(config: AsyncConfig, outputs: OutputsRef) -> Self {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                items: VecDeque::with_capacity(config.capacity),
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let worker_queue = queue.clone();
+        let worker = std::thread::Builder::new()
+            .name(String::from("ulogger-writer"))
+            .spawn(move || run(&worker_queue, &outputs))
+            .expect("failed to spawn the ulogger writer thread");
+
+        Self {
+            queue,
+            config,
+            dropped: 0,
+            worker: Some(worker),
+        }
+    }
+
+    pub(crate) fn push(&mut self, record: OwnedRecord) {
+        let capacity = self.config.capacity.max(1);
+        let mut state = self.queue.lock();
+
+        if state.items.len() >= capacity {
+            match self.config.overflow {
+                OverflowPolicy::Block => {
+                    while state.items.len() >= capacity && !state.closed {
+                        state = self
+                            .queue
+                            .changed
+                            .wait(state)
+                            .unwrap_or_else(|e| e.into_inner());
+                    }
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped += 1;
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    let oldest = state
+                        .items
+                        .iter()
+                        .position(|m| matches!(m, Message::Record(_)));
+                    if let Some(pos) = oldest {
+                        state.items.remove(pos);
+                        self.dropped += 1;
+                    }
+                }
+            }
+        }
+
+        state.items.push_back(Message::Record(record));
+        self.queue.changed.notify_all();
+    }
+
+    /// Blocks until everything queued so far is written and flushed.
+    pub(crate) fn flush(&self) {
+        let (ack, done) = mpsc::channel();
+        {
+            let mut state = self.queue.lock();
+            state.items.push_back(Message::Flush(ack));
+            self.queue.changed.notify_all();
+        }
+        let _ = done.recv();
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl Drop for AsyncWriter {
+    /// Drains the queue and stops the writer thread.
+    fn drop(&mut self) {
+        self.queue.lock().closed = true;
+        self.queue.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run(queue: &Queue, outputs: &OutputsRef) {
+    loop {
+        let message = {
+            let mut state = queue.lock();
+            while state.items.is_empty() && !state.closed {
+                state = queue.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            match state.items.pop_front() {
+                Some(message) => message,
+                None => return,
+            }
+        };
+        queue.changed.notify_all();
+
+        let mut outputs = outputs.lock().unwrap_or_else(|e| e.into_inner());
+        match message {
+            Message::Record(record) => outputs.dispatch(&record.as_record()),
+            Message::Flush(ack) => {
+                outputs.flush();
+                let _ = ack.send(());
+            }
+        }
+    }
+}
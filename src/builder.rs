@@ -0,0 +1,162 @@
+use crate::{
+    AsyncConfig, ErrorHandler, FileFallback, FlushPolicy, LogFileConfig, LogFormat, LogLevel,
+    Logger, LoggerConfig, LoggerError, RotationPolicy, Sink,
+};
+
+// ---------- Logger builder ----------
+/// Collects settings and checks them together in `build`/`install`:
+///
+/// ```no_run
+/// use ulogger::{LogLevel, LogFileConfig, LoggerBuilder};
+///
+/// LoggerBuilder::new()
+///     .console_threshold(LogLevel::Info)
+///     .use_colors(false)
+///     .file(LogFileConfig::default())
+///     .module_threshold("NET", LogLevel::Verbose)
+///     .install()
+///     .expect("invalid logger settings");
+/// ```
+#[derive(Default)]
+pub struct LoggerBuilder {
+    config: LoggerConfig,
+    sinks: Vec<Box<dyn Sink>>,
+    error_handler: Option<ErrorHandler>,
+}
+
+impl LoggerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from settings loaded with `LoggerConfig::load`/`parse`.
+    pub fn from_config(config: LoggerConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn include_date(mut self, enabled: bool) -> Self {
+        self.config.include_date = enabled;
+        self
+    }
+
+    pub fn console_threshold(mut self, level: LogLevel) -> Self {
+        self.config.console_threshold = level;
+        self
+    }
+
+    pub fn console_max_level(mut self, level: LogLevel) -> Self {
+        self.config.console_max_level = Some(level);
+        self
+    }
+
+    pub fn console_format(mut self, format: LogFormat) -> Self {
+        self.config.console_format = format;
+        self
+    }
+
+    pub fn use_colors(mut self, enabled: bool) -> Self {
+        self.config.use_colors = enabled;
+        self
+    }
+
+    /// Enables file logging with the given location.
+    pub fn file(mut self, config: LogFileConfig) -> Self {
+        self.config.file = config;
+        self.config.file_enabled = true;
+        self
+    }
+
+    pub fn file_threshold(mut self, level: LogLevel) -> Self {
+        self.config.file_threshold = level;
+        self
+    }
+
+    pub fn file_max_level(mut self, level: LogLevel) -> Self {
+        self.config.file_max_level = Some(level);
+        self
+    }
+
+    pub fn file_format(mut self, format: LogFormat) -> Self {
+        self.config.file_format = format;
+        self
+    }
+
+    pub fn use_icons_in_file(mut self, enabled: bool) -> Self {
+        self.config.use_icons_in_file = enabled;
+        self
+    }
+
+    pub fn file_rotation(mut self, policy: RotationPolicy) -> Self {
+        self.config.file_rotation = Some(policy);
+        self
+    }
+
+    pub fn file_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.config.file_flush_policy = policy;
+        self
+    }
+
+    pub fn file_fallback(mut self, fallback: FileFallback) -> Self {
+        self.config.file_fallback = fallback;
+        self
+    }
+
+    pub fn default_module_threshold(mut self, level: LogLevel) -> Self {
+        self.config.default_module_threshold = level;
+        self
+    }
+
+    pub fn module_threshold(mut self, module: impl Into<String>, level: LogLevel) -> Self {
+        let module = module.into();
+        self.config.module_thresholds.retain(|(m, _)| *m != module);
+        self.config.module_thresholds.push((module, level));
+        self
+    }
+
+    pub fn async_writer(mut self, config: AsyncConfig) -> Self {
+        self.config.async_writer = Some(config);
+        self
+    }
+
+    pub fn sink(mut self, sink: Box<dyn Sink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn error_handler(mut self, handler: ErrorHandler) -> Self {
+        self.error_handler = Some(handler);
+        self
+    }
+
+    /// Validates the settings and creates a standalone logger.
+    pub fn build(self) -> Result<Logger, LoggerError> {
+        self.config.validate()?;
+        let mut logger = Logger::new();
+        logger.set_error_handler(self.error_handler);
+        for sink in self.sinks {
+            logger.add_sink(sink);
+        }
+        logger.apply_config(&self.config)?;
+        Ok(logger)
+    }
+
+    /// Validates the settings and replaces the global `LOGGER` with the result.
+    /// On error the current global logger is left untouched.
+    pub fn install(self) -> Result<(), LoggerError> {
+        let logger = self.build()?;
+        let previous = {
+            let mut global = crate::LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+            let level_hint = global.level_hint.clone();
+            let previous = std::mem::replace(&mut *global, logger);
+            global.level_hint = level_hint;
+            global.refresh_level_hint();
+            previous
+        };
+        // Drained and flushed outside the lock
+        drop(previous);
+        Ok(())
+    }
+}
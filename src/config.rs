@@ -0,0 +1,555 @@
+use crate::{
+    AsyncConfig, FileFallback, FlushPolicy, LinePattern, LogFileConfig, LogFormat, LogLevel,
+    LoggerError, OverflowPolicy, RotationInterval, RotationNaming, RotationPolicy,
+};
+use std::path::Path;
+use toml::{Table, Value};
+
+// ---------- Logger configuration ----------
+/// Every setting a configuration file can describe. `Default` matches a
+/// freshly created `Logger`.
+///
+/// ```toml
+/// include_date = true
+/// default_level = "warning"
+///
+/// [console]
+/// threshold = "info"
+/// colors = true
+/// format = "pattern"
+/// line_pattern = "%d{%H:%M:%S} %l{:>7} %m{:<8} %msg"
+///
+/// [file]
+/// enabled = true
+/// directory = "logs"
+/// pattern = "app_%Y%m%d.log"
+/// append = true
+/// threshold = "debug"
+/// format = "json"
+///
+/// [file.rotation]
+/// max_bytes = 10485760
+/// max_files = 5
+/// compress = true
+///
+/// [modules]
+/// NET = "verbose"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggerConfig {
+    pub include_date: bool,
+    pub console_threshold: LogLevel,
+    pub console_max_level: Option<LogLevel>,
+    pub console_format: LogFormat,
+    pub use_colors: bool,
+    pub file_enabled: bool,
+    pub file: LogFileConfig,
+    pub file_threshold: LogLevel,
+    pub file_max_level: Option<LogLevel>,
+    pub file_format: LogFormat,
+    pub use_icons_in_file: bool,
+    pub file_rotation: Option<RotationPolicy>,
+    pub file_flush_policy: FlushPolicy,
+    pub file_fallback: FileFallback,
+    pub default_module_threshold: LogLevel,
+    /// Sorted by module name.
+    pub module_thresholds: Vec<(String, LogLevel)>,
+    pub async_writer: Option<AsyncConfig>,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            include_date: true,
+            console_threshold: LogLevel::Verbose,
+            console_max_level: None,
+            console_format: LogFormat::Text,
+            use_colors: true,
+            file_enabled: false,
+            file: LogFileConfig::default(),
+            file_threshold: LogLevel::Verbose,
+            file_max_level: None,
+            file_format: LogFormat::Text,
+            use_icons_in_file: false,
+            file_rotation: None,
+            file_flush_policy: FlushPolicy::default(),
+            file_fallback: FileFallback::Discard,
+            default_module_threshold: LogLevel::Verbose,
+            module_thresholds: Vec::new(),
+            async_writer: None,
+        }
+    }
+}
+
+impl LoggerConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoggerError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| LoggerError::ReadConfig {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parses a TOML document. Unknown keys and invalid values are rejected
+    /// with the dotted path of the offending key.
+    pub fn parse(text: &str) -> Result<Self, LoggerError> {
+        let table: Table =
+            text.parse()
+                .map_err(|e: toml::de::Error| LoggerError::InvalidConfig {
+                    key: String::new(),
+                    reason: e.message().to_string(),
+                })?;
+
+        let mut config = Self::default();
+        let root = Section::root(&table);
+        root.allow(&[
+            "include_date",
+            "default_level",
+            "console",
+            "file",
+            "modules",
+            "async",
+        ])?;
+        set(&mut config.include_date, root.bool("include_date")?);
+        set(
+            &mut config.default_module_threshold,
+            root.level("default_level")?,
+        );
+
+        if let Some(console) = root.section("console")? {
+            console.allow(&["threshold", "max_level", "colors", "format", "line_pattern"])?;
+            set(&mut config.console_threshold, console.level("threshold")?);
+            config.console_max_level = console.level("max_level")?;
+            set(&mut config.use_colors, console.bool("colors")?);
+            set(&mut config.console_format, console.format()?);
+        }
+
+        if let Some(file) = root.section("file")? {
+            file.allow(&[
+                "enabled",
+                "directory",
+                "pattern",
+                "append",
+                "threshold",
+                "max_level",
+                "format",
+                "line_pattern",
+                "icons",
+                "fallback",
+                "rotation",
+                "flush",
+            ])?;
+            set(&mut config.file_enabled, file.bool("enabled")?);
+            config.file.directory = file.str("directory")?.map(Into::into);
+            set(
+                &mut config.file.pattern,
+                file.str("pattern")?.map(Into::into),
+            );
+            set(&mut config.file.append, file.bool("append")?);
+            set(&mut config.file_threshold, file.level("threshold")?);
+            config.file_max_level = file.level("max_level")?;
+            set(&mut config.file_format, file.format()?);
+            set(&mut config.use_icons_in_file, file.bool("icons")?);
+            set(
+                &mut config.file_fallback,
+                file.choice(
+                    "fallback",
+                    &[
+                        ("discard", FileFallback::Discard),
+                        ("stderr", FileFallback::Stderr),
+                    ],
+                )?,
+            );
+
+            if let Some(rotation) = file.section("rotation")? {
+                config.file_rotation = Some(rotation.rotation()?);
+            }
+            if let Some(flush) = file.section("flush")? {
+                config.file_flush_policy = flush.flush_policy()?;
+            }
+        }
+
+        if let Some(modules) = root.section("modules")? {
+            for name in modules.table.keys() {
+                if let Some(level) = modules.level(name)? {
+                    config.module_thresholds.push((name.clone(), level));
+                }
+            }
+        }
+
+        if let Some(writer) = root.section("async")? {
+            writer.allow(&["capacity", "overflow"])?;
+            let mut async_config = AsyncConfig::default();
+            set(&mut async_config.capacity, writer.usize("capacity")?);
+            set(
+                &mut async_config.overflow,
+                writer.choice(
+                    "overflow",
+                    &[
+                        ("block", OverflowPolicy::Block),
+                        ("drop_newest", OverflowPolicy::DropNewest),
+                        ("drop_oldest", OverflowPolicy::DropOldest),
+                    ],
+                )?,
+            );
+            config.async_writer = Some(async_config);
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// One `key: old -> new` entry per setting that differs in `other`.
+    pub(crate) fn diff(&self, other: &Self) -> Vec<String> {
+        fn change<T: PartialEq + std::fmt::Debug>(
+            changes: &mut Vec<String>,
+            key: &str,
+            old: &T,
+            new: &T,
+        ) {
+            if old != new {
+                changes.push(format!("{}: {:?} -> {:?}", key, old, new));
+            }
+        }
+
+        let mut changes = Vec::new();
+        let c = &mut changes;
+        change(c, "include_date", &self.include_date, &other.include_date);
+        change(
+            c,
+            "default_level",
+            &self.default_module_threshold,
+            &other.default_module_threshold,
+        );
+        change(
+            c,
+            "console.threshold",
+            &self.console_threshold,
+            &other.console_threshold,
+        );
+        change(
+            c,
+            "console.max_level",
+            &self.console_max_level,
+            &other.console_max_level,
+        );
+        change(
+            c,
+            "console.format",
+            &self.console_format,
+            &other.console_format,
+        );
+        change(c, "console.colors", &self.use_colors, &other.use_colors);
+        change(c, "file.enabled", &self.file_enabled, &other.file_enabled);
+        change(
+            c,
+            "file.directory",
+            &self.file.directory,
+            &other.file.directory,
+        );
+        change(c, "file.pattern", &self.file.pattern, &other.file.pattern);
+        change(c, "file.append", &self.file.append, &other.file.append);
+        change(
+            c,
+            "file.threshold",
+            &self.file_threshold,
+            &other.file_threshold,
+        );
+        change(
+            c,
+            "file.max_level",
+            &self.file_max_level,
+            &other.file_max_level,
+        );
+        change(c, "file.format", &self.file_format, &other.file_format);
+        change(
+            c,
+            "file.icons",
+            &self.use_icons_in_file,
+            &other.use_icons_in_file,
+        );
+        change(
+            c,
+            "file.fallback",
+            &self.file_fallback,
+            &other.file_fallback,
+        );
+        change(
+            c,
+            "file.rotation",
+            &self.file_rotation,
+            &other.file_rotation,
+        );
+        change(
+            c,
+            "file.flush",
+            &self.file_flush_policy,
+            &other.file_flush_policy,
+        );
+        change(c, "async", &self.async_writer, &other.async_writer);
+
+        let level = |config: &Self, module: &str| {
+            config
+                .module_thresholds
+                .iter()
+                .find(|(m, _)| m == module)
+                .map(|(_, level)| *level)
+        };
+        let mut modules: Vec<&str> = self
+            .module_thresholds
+            .iter()
+            .chain(&other.module_thresholds)
+            .map(|(m, _)| m.as_str())
+            .collect();
+        modules.sort_unstable();
+        modules.dedup();
+        for module in modules {
+            let key = format!("modules.{}", module);
+            change(c, &key, &level(self, module), &level(other, module));
+        }
+
+        changes
+    }
+
+    /// Checks settings that are well-formed on their own but inconsistent or
+    /// unusable; errors name the key as it would appear in a config file.
+    pub fn validate(&self) -> Result<(), LoggerError> {
+        let error = |key: &str, reason: &str| LoggerError::InvalidConfig {
+            key: key.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if self
+            .console_max_level
+            .is_some_and(|max| max < self.console_threshold)
+        {
+            return Err(error("console.max_level", "below console.threshold"));
+        }
+        if self
+            .file_max_level
+            .is_some_and(|max| max < self.file_threshold)
+        {
+            return Err(error("file.max_level", "below file.threshold"));
+        }
+        if self.file.pattern.is_empty() {
+            return Err(error("file.pattern", "must not be empty"));
+        }
+        if self.file.resolve_path().is_err() {
+            return Err(error("file.pattern", "invalid strftime specifier"));
+        }
+        if let Some(rotation) = &self.file_rotation {
+            if rotation.max_bytes == Some(0) {
+                return Err(error("file.rotation.max_bytes", "must be at least 1"));
+            }
+        }
+        if self
+            .module_thresholds
+            .iter()
+            .any(|(name, _)| name.is_empty())
+        {
+            return Err(error("modules", "empty module name"));
+        }
+        if self.async_writer.as_ref().is_some_and(|a| a.capacity == 0) {
+            return Err(error("async.capacity", "must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+fn set<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+// ---------- Table walking ----------
+/// A table together with its dotted path, for error messages.
+struct Section<'a> {
+    path: String,
+    table: &'a Table,
+}
+
+impl<'a> Section<'a> {
+    fn root(table: &'a Table) -> Self {
+        Self {
+            path: String::new(),
+            table,
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        }
+    }
+
+    fn error(&self, name: &str, reason: impl Into<String>) -> LoggerError {
+        LoggerError::InvalidConfig {
+            key: self.key(name),
+            reason: reason.into(),
+        }
+    }
+
+    fn allow(&self, known: &[&str]) -> Result<(), LoggerError> {
+        match self.table.keys().find(|k| !known.contains(&k.as_str())) {
+            Some(unknown) => Err(self.error(unknown, "unknown key")),
+            None => Ok(()),
+        }
+    }
+
+    fn section(&self, name: &str) -> Result<Option<Section<'a>>, LoggerError> {
+        match self.table.get(name) {
+            None => Ok(None),
+            Some(Value::Table(table)) => Ok(Some(Section {
+                path: self.key(name),
+                table,
+            })),
+            Some(_) => Err(self.error(name, "expected a table")),
+        }
+    }
+
+    fn bool(&self, name: &str) -> Result<Option<bool>, LoggerError> {
+        match self.table.get(name) {
+            None => Ok(None),
+            Some(Value::Boolean(value)) => Ok(Some(*value)),
+            Some(_) => Err(self.error(name, "expected true or false")),
+        }
+    }
+
+    fn str(&self, name: &str) -> Result<Option<&'a str>, LoggerError> {
+        match self.table.get(name) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(self.error(name, "expected a string")),
+        }
+    }
+
+    fn u64(&self, name: &str) -> Result<Option<u64>, LoggerError> {
+        match self.table.get(name) {
+            None => Ok(None),
+            Some(Value::Integer(value)) => u64::try_from(*value)
+                .map(Some)
+                .map_err(|_| self.error(name, "expected a non-negative integer")),
+            Some(_) => Err(self.error(name, "expected an integer")),
+        }
+    }
+
+    fn usize(&self, name: &str) -> Result<Option<usize>, LoggerError> {
+        match self.u64(name)? {
+            None => Ok(None),
+            Some(value) => usize::try_from(value)
+                .map(Some)
+                .map_err(|_| self.error(name, "value is too large")),
+        }
+    }
+
+    fn level(&self, name: &str) -> Result<Option<LogLevel>, LoggerError> {
+        match self.str(name)? {
+            None => Ok(None),
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|e: crate::ParseLevelError| self.error(name, e.to_string())),
+        }
+    }
+
+    fn choice<T: Copy>(&self, name: &str, options: &[(&str, T)]) -> Result<Option<T>, LoggerError> {
+        let Some(value) = self.str(name)? else {
+            return Ok(None);
+        };
+        match options
+            .iter()
+            .find(|(option, _)| option.eq_ignore_ascii_case(value))
+        {
+            Some((_, choice)) => Ok(Some(*choice)),
+            None => {
+                let names: Vec<&str> = options.iter().map(|(option, _)| *option).collect();
+                Err(self.error(name, format!("expected one of: {}", names.join(", "))))
+            }
+        }
+    }
+
+    /// `format = "text" | "json" | "logfmt" | "pattern"`, the latter with `line_pattern`.
+    fn format(&self) -> Result<Option<LogFormat>, LoggerError> {
+        let line_pattern = match self.str("line_pattern")? {
+            Some(pattern) => Some(LinePattern::compile(pattern).map_err(|e| match e {
+                LoggerError::InvalidLinePattern { reason, .. } => {
+                    self.error("line_pattern", reason)
+                }
+                other => other,
+            })?),
+            None => None,
+        };
+
+        match (self.str("format")?, line_pattern) {
+            (None, None) => Ok(None),
+            (None | Some("pattern"), Some(pattern)) => Ok(Some(LogFormat::Pattern(pattern))),
+            (Some("pattern"), None) => {
+                Err(self.error("line_pattern", "required by format = \"pattern\""))
+            }
+            (Some(_), Some(_)) => {
+                Err(self.error("line_pattern", "only valid with format = \"pattern\""))
+            }
+            (Some("text"), None) => Ok(Some(LogFormat::Text)),
+            (Some("json"), None) => Ok(Some(LogFormat::Json)),
+            (Some("logfmt"), None) => Ok(Some(LogFormat::Logfmt)),
+            (Some(_), None) => {
+                Err(self.error("format", "expected one of: text, json, logfmt, pattern"))
+            }
+        }
+    }
+
+    fn rotation(&self) -> Result<RotationPolicy, LoggerError> {
+        self.allow(&[
+            "max_bytes",
+            "interval",
+            "naming",
+            "max_files",
+            "max_total_bytes",
+            "compress",
+        ])?;
+        let mut policy = RotationPolicy {
+            max_bytes: self.u64("max_bytes")?,
+            interval: self.choice(
+                "interval",
+                &[
+                    ("hourly", RotationInterval::Hourly),
+                    ("daily", RotationInterval::Daily),
+                ],
+            )?,
+            max_files: self.usize("max_files")?,
+            max_total_bytes: self.u64("max_total_bytes")?,
+            ..RotationPolicy::default()
+        };
+        set(
+            &mut policy.naming,
+            self.choice(
+                "naming",
+                &[
+                    ("numbered", RotationNaming::Numbered),
+                    ("dated", RotationNaming::Dated),
+                ],
+            )?,
+        );
+        set(&mut policy.compress, self.bool("compress")?);
+        Ok(policy)
+    }
+
+    fn flush_policy(&self) -> Result<FlushPolicy, LoggerError> {
+        self.allow(&["every_records", "interval_ms", "immediate_level", "sync"])?;
+        let mut policy = FlushPolicy::default();
+        if self.table.contains_key("every_records") {
+            // 0 leaves flushing to the other triggers
+            policy.every_records = self.usize("every_records")?.filter(|&n| n > 0);
+        }
+        policy.interval = self
+            .u64("interval_ms")?
+            .map(std::time::Duration::from_millis);
+        policy.immediate_level = self.level("immediate_level")?;
+        set(&mut policy.sync, self.bool("sync")?);
+        Ok(policy)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{LogLevel, LoggerError};
+
+/// Environment variable read by `log_init!`.
+pub const ENV_FILTER_VAR: &str = "ULOGGER";
+
+// ---------- Filter directives ----------
+/// Parsed `RUST_LOG`-style directives such as `warning,NET=verbose,DB=error`:
+/// a bare level sets the default, `MODULE=level` sets one module's threshold.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EnvFilter {
+    pub default: Option<LogLevel>,
+    pub modules: Vec<(String, LogLevel)>,
+}
+
+impl EnvFilter {
+    pub fn parse(spec: &str) -> Result<Self, LoggerError> {
+        let mut filter = Self::default();
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let error = || LoggerError::InvalidDirective(directive.to_string());
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    let module = module.trim();
+                    if module.is_empty() {
+                        return Err(error());
+                    }
+                    let level = level.parse().map_err(|_| error())?;
+                    filter.modules.retain(|(m, _)| m != module);
+                    filter.modules.push((module.to_string(), level));
+                }
+                None => filter.default = Some(directive.parse().map_err(|_| error())?),
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Reads and parses `var`; `None` when it is unset or empty.
+    pub fn from_env(var: &str) -> Option<Result<Self, LoggerError>> {
+        let spec = std::env::var(var).ok()?;
+        if spec.trim().is_empty() {
+            return None;
+        }
+        Some(Self::parse(&spec))
+    }
+
+    /// Most verbose level named by any directive.
+    pub(crate) fn min_level(&self) -> Option<LogLevel> {
+        self.default
+            .iter()
+            .chain(self.modules.iter().map(|(_, level)| level))
+            .copied()
+            .min()
+    }
+}
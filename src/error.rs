@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+// ---------- LoggerError ----------
+#[derive(Debug)]
+pub enum LoggerError {
+    /// The log file name pattern contains an invalid strftime specifier.
+    InvalidPattern(String),
+    /// A line pattern for `LogFormat::Pattern` could not be compiled.
+    InvalidLinePattern { pattern: String, reason: String },
+    /// A filter directive (e.g. from the `ULOGGER` variable) could not be parsed.
+    InvalidDirective(String),
+    /// A configuration file could not be read.
+    ReadConfig {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A configuration document is malformed; `key` is the dotted path of the
+    /// offending entry (empty for syntax errors).
+    InvalidConfig { key: String, reason: String },
+    /// The log file (or its directory) could not be created or opened.
+    OpenFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Writing, rotating or flushing the log file failed.
+    WriteFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A custom sink reported a failure.
+    Sink(std::io::Error),
+}
+
+impl std::fmt::Display for LoggerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoggerError::InvalidPattern(pattern) => {
+                write!(f, "invalid log file pattern '{}'", pattern)
+            }
+            LoggerError::InvalidLinePattern { pattern, reason } => {
+                write!(f, "invalid line pattern '{}': {}", pattern, reason)
+            }
+            LoggerError::InvalidDirective(directive) => {
+                write!(f, "invalid filter directive '{}'", directive)
+            }
+            LoggerError::ReadConfig { path, source } => {
+                write!(f, "cannot read config file {}: {}", path.display(), source)
+            }
+            LoggerError::InvalidConfig { key, reason } if key.is_empty() => {
+                write!(f, "invalid config: {}", reason)
+            }
+            LoggerError::InvalidConfig { key, reason } => {
+                write!(f, "invalid config key '{}': {}", key, reason)
+            }
+            LoggerError::OpenFile { path, source } => {
+                write!(f, "cannot open log file {}: {}", path.display(), source)
+            }
+            LoggerError::WriteFile { path, source } => {
+                write!(f, "cannot write log file {}: {}", path.display(), source)
+            }
+            LoggerError::Sink(source) => write!(f, "sink write failed: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for LoggerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoggerError::InvalidPattern(_)
+            | LoggerError::InvalidLinePattern { .. }
+            | LoggerError::InvalidDirective(_)
+            | LoggerError::InvalidConfig { .. } => None,
+            LoggerError::ReadConfig { source, .. }
+            | LoggerError::OpenFile { source, .. }
+            | LoggerError::WriteFile { source, .. }
+            | LoggerError::Sink(source) => Some(source),
+        }
+    }
+}
+
+/// Callback invoked for every open/write failure.
+pub type ErrorHandler = Box<dyn Fn(&LoggerError) + Send>;
+
+// ---------- File fallback ----------
+/// What happens to a record the log file could not take.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileFallback {
+    Discard,
+    Stderr,
+}
+
+// ---------- ParseLevelError ----------
+/// Returned when a string or number does not name a `LogLevel`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError(pub(crate) String);
+
+impl std::fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown log level '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
@@ -0,0 +1,78 @@
+// ---------- Structured field values ----------
+/// Typed value of a `key=value` field attached to a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // Quote text that would otherwise be ambiguous in `key=value` output
+            FieldValue::Str(s) if s.is_empty() || s.contains(char::is_whitespace) => {
+                write!(f, "{:?}", s)
+            }
+            FieldValue::Str(s) => write!(f, "{}", s),
+            FieldValue::I64(v) => write!(f, "{}", v),
+            FieldValue::U64(v) => write!(f, "{}", v),
+            FieldValue::F64(v) => write!(f, "{}", v),
+            FieldValue::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl From<char> for FieldValue {
+    fn from(value: char) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+macro_rules! field_value_from {
+    ($variant:ident as $target:ty: $($t:ty),+) => {
+        $(
+            impl From<$t> for FieldValue {
+                fn from(value: $t) -> Self {
+                    FieldValue::$variant(value as $target)
+                }
+            }
+        )+
+    };
+}
+
+field_value_from!(I64 as i64: i8, i16, i32, i64, isize);
+field_value_from!(U64 as u64: u8, u16, u32, u64, usize);
+field_value_from!(F64 as f64: f32, f64);
+
+/// Appends ` key=value` for every field to a text line.
+pub(crate) fn write_fields(out: &mut String, fields: &[(String, FieldValue)]) {
+    use std::fmt::Write as _;
+
+    for (key, value) in fields {
+        if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+        let _ = write!(out, "{}={}", key, value);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{FieldValue, LinePattern, Record};
+use chrono::SecondsFormat;
+use std::fmt::Write as _;
+
+// ---------- Output formats ----------
+/// Line layout used by the console and file outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// `timestamp | level | module | message`
+    #[default]
+    Text,
+    /// One JSON object per line (JSON Lines)
+    Json,
+    /// `ts=... level=info module=NET msg="..." key=value`
+    Logfmt,
+    /// User-defined layout, see `LinePattern`
+    Pattern(LinePattern),
+}
+
+impl LogFormat {
+    /// Renders the machine-readable formats; `Text` is laid out by each sink.
+    pub fn render(&self, record: &Record) -> Option<String> {
+        match self {
+            LogFormat::Text => None,
+            LogFormat::Json => Some(json(record)),
+            LogFormat::Logfmt => Some(logfmt(record)),
+            LogFormat::Pattern(pattern) => Some(pattern.render(record)),
+        }
+    }
+}
+
+// ---------- JSON Lines ----------
+fn json(record: &Record) -> String {
+    let mut out = String::with_capacity(128 + record.message.len());
+
+    out.push_str("{\"timestamp\":");
+    write_json_str(
+        &mut out,
+        &record.time.to_rfc3339_opts(SecondsFormat::Micros, false),
+    );
+    out.push_str(",\"level\":");
+    write_json_str(&mut out, record.level.as_str());
+    out.push_str(",\"module\":");
+    write_json_str(&mut out, record.module);
+    out.push_str(",\"message\":");
+    write_json_str(&mut out, record.message.trim_end());
+    out.push_str(",\"thread\":");
+    write_json_str(&mut out, record.thread);
+
+    if !record.fields.is_empty() {
+        out.push_str(",\"fields\":{");
+        for (i, (key, value)) in record.fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_json_str(&mut out, key);
+            out.push(':');
+            write_json_value(&mut out, value);
+        }
+        out.push('}');
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn write_json_value(out: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(s) => write_json_str(out, s),
+        FieldValue::I64(v) => {
+            let _ = write!(out, "{}", v);
+        }
+        FieldValue::U64(v) => {
+            let _ = write!(out, "{}", v);
+        }
+        // JSON has no NaN/inf, keep them readable as strings
+        FieldValue::F64(v) if !v.is_finite() => write_json_str(out, &v.to_string()),
+        FieldValue::F64(v) => {
+            let _ = write!(out, "{}", v);
+        }
+        FieldValue::Bool(v) => {
+            let _ = write!(out, "{}", v);
+        }
+    }
+}
+
+fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// ---------- logfmt ----------
+fn logfmt(record: &Record) -> String {
+    let mut out = String::with_capacity(128 + record.message.len());
+
+    out.push_str("ts=");
+    out.push_str(&record.time.to_rfc3339_opts(SecondsFormat::Micros, false));
+    out.push_str(" level=");
+    out.push_str(&record.level.as_str().to_lowercase());
+    out.push_str(" module=");
+    write_logfmt_str(&mut out, record.module);
+    out.push_str(" msg=");
+    write_logfmt_str(&mut out, record.message.trim_end());
+
+    for (key, value) in record.fields {
+        out.push(' ');
+        out.push_str(key);
+        out.push('=');
+        match value {
+            FieldValue::Str(s) => write_logfmt_str(&mut out, s),
+            other => {
+                let _ = write!(out, "{}", other);
+            }
+        }
+    }
+
+    out.push('\n');
+    out
+}
+
+fn write_logfmt_str(out: &mut String, s: &str) {
+    let needs_quotes = s.is_empty()
+        || s.chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+
+    if !needs_quotes {
+        out.push_str(s);
+        return;
+    }
+
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:04x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,94 @@
+use crate::{LevelHint, LogLevel, Logger};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// ---------- Logger handle ----------
+/// A cheap, clonable reference to a `Logger` that is independent of the
+/// global `LOGGER`. Pass it to the macros with `target:`:
+///
+/// ```
+/// use ulogger::{log_info, LoggerBuilder, LoggerHandle, LogLevel};
+///
+/// let db = LoggerHandle::from(LoggerBuilder::new().use_colors(false).build().unwrap());
+/// log_info!(target: &db, "connected to {}", "primary");
+/// db.lock().set_console_threshold(LogLevel::Warning);
+/// ```
+#[derive(Clone)]
+pub struct LoggerHandle {
+    logger: Arc<Mutex<Logger>>,
+    level_hint: Arc<LevelHint>,
+    /// Registered ancestors, nearest first; set on handles from the registry.
+    ancestors: Arc<[LoggerHandle]>,
+}
+
+impl LoggerHandle {
+    pub fn new(logger: Logger) -> Self {
+        Self {
+            level_hint: logger.level_hint.clone(),
+            logger: Arc::new(Mutex::new(logger)),
+            ancestors: Arc::new([]),
+        }
+    }
+
+    /// A handle to the global `LOGGER`.
+    pub fn global() -> Self {
+        Self {
+            logger: crate::LOGGER.clone(),
+            level_hint: crate::LEVEL_HINT.clone(),
+            ancestors: Arc::new([]),
+        }
+    }
+
+    pub(crate) fn with_ancestors(mut self, ancestors: Vec<LoggerHandle>) -> Self {
+        self.ancestors = ancestors.into();
+        self
+    }
+
+    pub(crate) fn same_logger(&self, other: &LoggerHandle) -> bool {
+        Arc::ptr_eq(&self.logger, &other.logger)
+    }
+
+    /// Locks the logger, recovering from a poisoned lock.
+    pub fn lock(&self) -> MutexGuard<'_, Logger> {
+        self.logger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Like `ulogger::max_enabled_level`, for this logger and the ancestors
+    /// its records propagate to. Read without locking.
+    pub fn max_enabled_level(&self) -> LogLevel {
+        if self.ancestors.is_empty() {
+            return crate::level_from_hint(&self.level_hint);
+        }
+
+        // Mirrors `print_propagated`: the module threshold comes from this
+        // logger or an ancestor's module entry, then every output applies
+        let hints = self.ancestors.iter().map(|ancestor| &*ancestor.level_hint);
+        let outputs = hints
+            .clone()
+            .map(LevelHint::outputs)
+            .fold(self.level_hint.outputs(), std::cmp::min);
+        let gate = hints
+            .map(LevelHint::modules)
+            .chain([self.level_hint.modules(), self.level_hint.default_module()])
+            .fold(LogLevel::Fixed, std::cmp::min);
+        crate::capped(std::cmp::max(outputs, gate))
+    }
+
+    /// Prints the record pending in `logger`, locked from this handle, and
+    /// propagates it to the registered ancestors. Used by the logging macros.
+    #[doc(hidden)]
+    pub fn print(&self, logger: &mut Logger) {
+        let module = crate::LOG_MODULE.with(|m| *m.borrow());
+        self.print_with_module(logger, module);
+    }
+
+    #[doc(hidden)]
+    pub fn print_with_module(&self, logger: &mut Logger, module: &str) {
+        logger.print_propagated(module, &self.ancestors);
+    }
+}
+
+impl From<Logger> for LoggerHandle {
+    fn from(logger: Logger) -> Self {
+        Self::new(logger)
+    }
+}
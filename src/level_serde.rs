@@ -0,0 +1,53 @@
+use crate::LogLevel;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+// ---------- serde ----------
+/// Serialized as the unpadded name (`"WARNING"`).
+impl Serialize for LogLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Accepts anything `FromStr` does, or the numeric value in human-readable
+/// formats.
+impl<'de> Deserialize<'de> for LogLevel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LevelVisitor;
+
+        impl Visitor<'_> for LevelVisitor {
+            type Value = LogLevel;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a log level name or number")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<LogLevel, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<LogLevel, E> {
+                u8::try_from(value)
+                    .ok()
+                    .and_then(|v| LogLevel::try_from(v).ok())
+                    .ok_or_else(|| E::custom(format!("unknown log level '{}'", value)))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<LogLevel, E> {
+                match u64::try_from(value) {
+                    Ok(value) => self.visit_u64(value),
+                    Err(_) => Err(E::custom(format!("unknown log level '{}'", value))),
+                }
+            }
+        }
+
+        // Compact formats (bincode, ...) cannot describe themselves; they only
+        // ever hold the name written by `serialize`
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(LevelVisitor)
+        } else {
+            deserializer.deserialize_str(LevelVisitor)
+        }
+    }
+}
@@ -1,14 +1,65 @@
 // Cargo.toml dependencies:
 // chrono = "0.4"
+// flate2 = "1.0"
 // lazy_static = "1.4"
+// log = { version = "0.4", optional = true }
+// tracing-core = { version = "0.1", optional = true }
+// tracing-subscriber = { version = "0.3", optional = true }
+// serde = { version = "1", optional = true }
+// toml = "0.8"
 
-use chrono::Local;
+use chrono::{DateTime, Local};
 use std::cell::RefCell;
-use std::io::Write;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 
+mod async_writer;
+mod builder;
+mod config;
+mod env_filter;
+mod error;
+mod field;
+mod format;
+mod handle;
+#[cfg(feature = "serde")]
+mod level_serde;
+#[cfg(feature = "log")]
+mod log_bridge;
+mod outputs;
+mod pattern;
+mod registry;
+mod reload;
+mod rotation;
+mod sink;
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+
+pub use async_writer::{AsyncConfig, OverflowPolicy};
+pub use builder::LoggerBuilder;
+pub use config::LoggerConfig;
+pub use env_filter::{EnvFilter, ENV_FILTER_VAR};
+pub use error::{ErrorHandler, FileFallback, LoggerError, ParseLevelError};
+pub use field::FieldValue;
+pub use format::LogFormat;
+pub use handle::LoggerHandle;
+#[cfg(feature = "log")]
+pub use log_bridge::{init_log_bridge, LogBridge};
+pub use pattern::LinePattern;
+#[doc(hidden)]
+pub use registry::module_logger;
+pub use registry::{named_logger, register_logger, unregister_logger};
+pub use reload::ConfigWatcher;
+pub use rotation::{RotationInterval, RotationNaming, RotationPolicy};
+pub use sink::{ConsoleSink, FileSink, FlushPolicy, LogFileConfig, Record, Sink};
+#[cfg(feature = "tracing")]
+pub use tracing_layer::UloggerLayer;
+
+use async_writer::AsyncWriter;
+use outputs::{IntervalFlusher, Outputs, OutputsRef, OwnedRecord};
+
 // ---------- LogLevel ----------
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LogLevel {
     Verbose,
     Debug,
@@ -19,22 +70,75 @@ pub enum LogLevel {
     Fixed,
 }
 
+/// Right-aligned to 7 characters for the column layout; see `as_str` for the bare name.
 impl std::fmt::Display for LogLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            LogLevel::Verbose => "VERBOSE",
-            LogLevel::Debug => "  DEBUG",
-            LogLevel::Info => "   INFO",
-            LogLevel::Warning => "WARNING",
-            LogLevel::Error => "  ERROR",
-            LogLevel::Fatal => "  FATAL",
-            LogLevel::Fixed => "  FIXED",
-        };
-        write!(f, "{}", s)
+        write!(f, "{:>7}", self.as_str())
+    }
+}
+
+/// Case-insensitive; accepts full names, unambiguous prefixes such as
+/// `"warn"` or `"err"`, and `"trace"` as an alias for `Verbose`.
+impl std::str::FromStr for LogLevel {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, ParseLevelError> {
+        let name = s.trim().to_ascii_uppercase();
+        if name == "TRACE" {
+            return Ok(LogLevel::Verbose);
+        }
+
+        let mut matches = LogLevel::ALL
+            .into_iter()
+            .filter(|level| !name.is_empty() && level.as_str().starts_with(&name));
+        match (matches.next(), matches.next()) {
+            (Some(level), None) => Ok(level),
+            _ => Err(ParseLevelError(s.to_string())),
+        }
+    }
+}
+
+impl From<LogLevel> for u8 {
+    fn from(level: LogLevel) -> u8 {
+        level as u8
+    }
+}
+
+impl TryFrom<u8> for LogLevel {
+    type Error = ParseLevelError;
+
+    fn try_from(value: u8) -> Result<Self, ParseLevelError> {
+        LogLevel::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| ParseLevelError(value.to_string()))
     }
 }
 
 impl LogLevel {
+    const ALL: [LogLevel; 7] = [
+        LogLevel::Verbose,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+        LogLevel::Fatal,
+        LogLevel::Fixed,
+    ];
+
+    /// Unpadded upper-case name, e.g. `"DEBUG"`; parses back with `FromStr`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Verbose => "VERBOSE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+            LogLevel::Fixed => "FIXED",
+        }
+    }
+
     fn color(&self) -> &'static str {
         match self {
             LogLevel::Verbose => "\x1b[90m", // Bright black / gray
@@ -73,40 +177,141 @@ macro_rules! log_module {
     }};
 }
 
+// ---------- Level hint ----------
+/// Lock-free summary of a logger's thresholds, read by the macros before
+/// taking the lock. The parts let `LoggerHandle` combine registered loggers.
+pub(crate) struct LevelHint {
+    /// Most verbose level that can reach any output.
+    level: AtomicU8,
+    /// Lowest output threshold.
+    outputs: AtomicU8,
+    default_module: AtomicU8,
+    /// Lowest per-module threshold, `Fixed` when there is none.
+    modules: AtomicU8,
+}
+
+impl LevelHint {
+    fn new() -> Self {
+        Self {
+            level: AtomicU8::new(LogLevel::Verbose as u8),
+            outputs: AtomicU8::new(LogLevel::Verbose as u8),
+            default_module: AtomicU8::new(LogLevel::Verbose as u8),
+            modules: AtomicU8::new(LogLevel::Fixed as u8),
+        }
+    }
+
+    fn load(level: &AtomicU8) -> LogLevel {
+        LogLevel::ALL[level.load(Ordering::Relaxed) as usize]
+    }
+
+    pub(crate) fn outputs(&self) -> LogLevel {
+        Self::load(&self.outputs)
+    }
+
+    pub(crate) fn default_module(&self) -> LogLevel {
+        Self::load(&self.default_module)
+    }
+
+    pub(crate) fn modules(&self) -> LogLevel {
+        Self::load(&self.modules)
+    }
+}
+
 // ---------- Logger Struct ----------
 pub struct Logger {
     buffer: String,
+    fields: Vec<(String, FieldValue)>,
+    location: Option<(&'static str, u32)>,
     current_level: LogLevel,
-    pub console_threshold: LogLevel,
-    pub file_threshold: LogLevel,
-    pub file_logging_enabled: bool,
-    pub use_colors: bool,
-    pub include_date: bool,
-    pub use_icons_in_file: bool,
-    log_file: Option<std::fs::File>,
-    pub log_file_path: Option<String>,
+    include_date: bool,
+    outputs: OutputsRef,
+    pub(crate) level_hint: Arc<LevelHint>,
+    writer: Option<AsyncWriter>,
+    flusher: Option<IntervalFlusher>,
+    module_thresholds: HashMap<String, LogLevel>,
+    default_module_threshold: LogLevel,
+    file_config: LogFileConfig,
+    file_threshold: LogLevel,
+    file_max_level: Option<LogLevel>,
+    file_rotation: Option<RotationPolicy>,
+    file_format: LogFormat,
+    file_flush_policy: FlushPolicy,
+    use_icons_in_file: bool,
 }
 
 impl Logger {
     pub fn new() -> Self {
         Self {
             buffer: String::with_capacity(1024),
+            fields: Vec::new(),
+            location: None,
             current_level: LogLevel::Info,
-            console_threshold: LogLevel::Verbose,
-            file_threshold: LogLevel::Verbose,
-            file_logging_enabled: false,
-            use_colors: true,
             include_date: true,
+            outputs: Arc::new(Mutex::new(Outputs::new())),
+            level_hint: Arc::new(LevelHint::new()),
+            writer: None,
+            flusher: None,
+            module_thresholds: HashMap::new(),
+            default_module_threshold: LogLevel::Verbose,
+            file_config: LogFileConfig::default(),
+            file_threshold: LogLevel::Verbose,
+            file_max_level: None,
+            file_rotation: None,
+            file_format: LogFormat::Text,
+            file_flush_policy: FlushPolicy::default(),
             use_icons_in_file: false,
-            log_file: None,
-            log_file_path: None,
         }
     }
 
+    fn with_level_hint(level_hint: Arc<LevelHint>) -> Self {
+        let logger = Self {
+            level_hint,
+            ..Self::new()
+        };
+        logger.refresh_level_hint();
+        logger
+    }
+
+    fn outputs(&self) -> std::sync::MutexGuard<'_, Outputs> {
+        self.outputs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Publishes the most verbose level any output and module accepts, so
+    /// disabled records can be skipped without taking the logger lock.
+    pub(crate) fn refresh_level_hint(&self) {
+        let outputs = self.outputs().min_threshold();
+        let modules = self
+            .module_thresholds
+            .values()
+            .fold(
+                LogLevel::Fixed,
+                |min, &level| if level < min { level } else { min },
+            );
+        let gate = if modules < self.default_module_threshold {
+            modules
+        } else {
+            self.default_module_threshold
+        };
+        let level = if outputs > gate { outputs } else { gate };
+
+        let hint = &self.level_hint;
+        hint.outputs.store(outputs as u8, Ordering::Relaxed);
+        hint.default_module
+            .store(self.default_module_threshold as u8, Ordering::Relaxed);
+        hint.modules.store(modules as u8, Ordering::Relaxed);
+        hint.level.store(level as u8, Ordering::Relaxed);
+    }
+
     pub fn append<T: std::fmt::Display>(&mut self, value: T) {
         self.buffer.push_str(&format!("{} ", value));
     }
 
+    /// Appends preformatted text as-is, without the trailing separator.
+    pub fn append_args(&mut self, args: std::fmt::Arguments) {
+        use std::fmt::Write as _;
+        let _ = self.buffer.write_fmt(args);
+    }
+
     pub fn append_bool(&mut self, value: bool) {
         self.buffer.push_str(if value { "true " } else { "false " });
     }
@@ -115,13 +320,19 @@ impl Logger {
         self.buffer.push_str(&format!("0x{:X} ", value));
     }
 
+    /// Attaches a structured field, kept apart from the message text.
+    pub fn append_kv(&mut self, key: impl Into<String>, value: impl Into<FieldValue>) {
+        self.fields.push((key.into(), value.into()));
+    }
+
     fn reset(&mut self) {
         self.buffer.clear();
+        self.fields.clear();
+        self.location = None;
         self.current_level = LogLevel::Info;
     }
 
-    fn timestamp(&self) -> String {
-        let now = Local::now();
+    fn timestamp(&self, now: &DateTime<Local>) -> String {
         if self.include_date {
             format!("{} | ", now.format("%Y-%m-%d %H:%M:%S%.6f"))
         } else {
@@ -130,84 +341,449 @@ impl Logger {
     }
 
     pub fn print(&mut self) {
-        let timestamp = self.timestamp();
-
         // Get module name from thread-local storage
         let module = crate::LOG_MODULE.with(|m| *m.borrow());
+        self.print_with_module(module);
+    }
 
-        // Console output
-        if self.current_level >= self.console_threshold {
-            let msg = if self.use_colors {
-                format!(
-                    "{}{}{:>8} | {:<8} | {}\x1b[0m\n",
-                    self.current_level.color(),
-                    timestamp,
-                    self.current_level,
-                    module,
-                    self.buffer
-                )
-            } else {
-                format!(
-                    "{}{:>8} | {:<8} | {}\n",
-                    timestamp, self.current_level, module, self.buffer
-                )
-            };
-            print!("{}", msg);
+    /// Like `print`, but with an explicit module column instead of `LOG_MODULE`.
+    pub fn print_with_module(&mut self, module: &str) {
+        self.print_propagated(module, &[]);
+    }
+
+    /// `print_with_module` for a logger resolved through the registry: the
+    /// record is also handed to its registered `ancestors` (nearest first).
+    /// The module threshold comes from this logger's entries, then the nearest
+    /// ancestor's, then this logger's default; ancestors' defaults never apply.
+    pub(crate) fn print_propagated(&mut self, module: &str, ancestors: &[LoggerHandle]) {
+        let threshold = self
+            .own_module_threshold(module)
+            .or_else(|| {
+                ancestors
+                    .iter()
+                    .find_map(|ancestor| ancestor.lock().own_module_threshold(module))
+            })
+            .unwrap_or(self.default_module_threshold);
+        if self.current_level < threshold {
+            self.reset();
+            return;
         }
 
-        // File output
-        if self.file_logging_enabled {
-            if let Some(file) = &mut self.log_file {
-                let level_repr = if self.use_icons_in_file {
-                    self.current_level.icon()
-                } else {
-                    &self.current_level.to_string()
-                };
+        let time = Local::now();
+        let timestamp = self.timestamp(&time);
 
-                let file_message = format!(
-                    "{}{:>8} | {:<8} | {}\n",
-                    timestamp, level_repr, module, self.buffer
-                );
+        let thread = std::thread::current();
+        let thread_id;
+        let thread_name = match thread.name() {
+            Some(name) => name,
+            None => {
+                thread_id = format!("{:?}", thread.id());
+                &thread_id
+            }
+        };
+
+        // Ancestors format the timestamp their own way in `forward`
+        let propagated = (!ancestors.is_empty()).then(|| OwnedRecord {
+            level: self.current_level,
+            time,
+            timestamp: String::new(),
+            module: module.to_string(),
+            thread: thread_name.to_string(),
+            location: self.location,
+            message: self.buffer.clone(),
+            fields: self.fields.clone(),
+        });
+
+        if let Some(writer) = &mut self.writer {
+            // Formatting and I/O happen on the writer thread
+            writer.push(OwnedRecord {
+                level: self.current_level,
+                time,
+                timestamp,
+                module: module.to_string(),
+                thread: thread_name.to_string(),
+                location: self.location,
+                message: self.buffer.clone(),
+                fields: std::mem::take(&mut self.fields),
+            });
+        } else {
+            let record = Record {
+                level: self.current_level,
+                time,
+                timestamp: &timestamp,
+                module,
+                thread: thread_name,
+                location: self.location,
+                message: &self.buffer,
+                fields: &self.fields,
+            };
+            self.outputs
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .dispatch(&record);
+        }
 
-                let _ = file.write_all(file_message.as_bytes());
+        if let Some(record) = propagated {
+            for ancestor in ancestors {
+                ancestor.lock().forward(record.clone());
             }
         }
 
         self.reset();
     }
 
+    /// Writes a record propagated from a registered descendant; only the
+    /// output thresholds of this logger apply.
+    fn forward(&mut self, mut record: OwnedRecord) {
+        record.timestamp = self.timestamp(&record.time);
+        match &mut self.writer {
+            Some(writer) => writer.push(record),
+            None => self.outputs().dispatch(&record.as_record()),
+        }
+    }
+
+    /// Whether a record at `level` would reach at least one output.
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        self.outputs().enabled(level)
+    }
+
+    /// Number of open/write failures since the logger was created.
+    pub fn error_count(&self) -> u64 {
+        self.outputs().error_count
+    }
+
+    pub fn set_error_handler(&mut self, handler: Option<ErrorHandler>) {
+        self.outputs().error_handler = handler;
+    }
+
+    pub fn set_file_fallback(&mut self, fallback: FileFallback) {
+        self.outputs().file_fallback = fallback;
+    }
+
+    /// Writes out everything logged so far, waiting for the async writer if enabled.
+    pub fn flush(&mut self) {
+        match &self.writer {
+            Some(writer) => writer.flush(),
+            None => self.outputs().flush(),
+        }
+    }
+
+    /// Moves formatting and writing to a background thread fed by a bounded queue.
+    pub fn enable_async(&mut self, config: AsyncConfig) {
+        self.disable_async();
+        self.writer = Some(AsyncWriter::start(config, self.outputs.clone()));
+    }
+
+    /// Drains the queue, stops the writer thread and returns to synchronous writes.
+    pub fn disable_async(&mut self) {
+        self.writer = None;
+    }
+
+    pub fn async_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Records discarded by the async overflow policy.
+    pub fn dropped_count(&self) -> u64 {
+        self.writer.as_ref().map_or(0, AsyncWriter::dropped)
+    }
+
+    /// The sink's threshold is read when it is added.
+    pub fn add_sink(&mut self, sink: Box<dyn Sink>) {
+        self.outputs().sinks.push(sink);
+        self.refresh_level_hint();
+    }
+
+    pub fn clear_sinks(&mut self) {
+        self.outputs().sinks.clear();
+        self.refresh_level_hint();
+    }
+
+    pub fn set_location(&mut self, file: &'static str, line: u32) {
+        self.location = Some((file, line));
+    }
+
     pub fn set_level(&mut self, level: LogLevel) {
         self.current_level = level;
     }
 
+    pub fn set_include_date(&mut self, enabled: bool) {
+        self.include_date = enabled;
+    }
+
+    pub fn include_date(&self) -> bool {
+        self.include_date
+    }
+
+    pub fn console_threshold(&self) -> LogLevel {
+        self.outputs().console.threshold
+    }
+
+    pub fn file_threshold(&self) -> LogLevel {
+        self.file_threshold
+    }
+
+    pub fn use_colors(&self) -> bool {
+        self.outputs().console.use_colors
+    }
+
+    pub fn use_icons_in_file(&self) -> bool {
+        self.use_icons_in_file
+    }
+
     pub fn set_console_threshold(&mut self, level: LogLevel) {
-        self.console_threshold = level;
+        self.outputs().console.threshold = level;
+        self.refresh_level_hint();
     }
 
     pub fn set_file_threshold(&mut self, level: LogLevel) {
         self.file_threshold = level;
+        if let Some(file) = &mut self.outputs().file {
+            file.threshold = level;
+        }
+        self.refresh_level_hint();
+    }
+
+    /// Records of `module` below `level` are dropped before reaching any output.
+    pub fn set_module_threshold(&mut self, module: impl Into<String>, level: LogLevel) {
+        self.module_thresholds.insert(module.into(), level);
+        self.refresh_level_hint();
+    }
+
+    /// `module` falls back to the default module threshold again.
+    pub fn clear_module_threshold(&mut self, module: &str) {
+        self.module_thresholds.remove(module);
+        self.refresh_level_hint();
+    }
+
+    pub fn clear_module_thresholds(&mut self) {
+        self.module_thresholds.clear();
+        self.refresh_level_hint();
+    }
+
+    /// Threshold for modules without their own entry (`Verbose` by default).
+    pub fn set_default_module_threshold(&mut self, level: LogLevel) {
+        self.default_module_threshold = level;
+        self.refresh_level_hint();
+    }
+
+    /// Dotted modules inherit from their parents: `"app.net.tls"` falls back
+    /// to `"app.net"`, then `"app"`, then the default.
+    pub fn module_threshold(&self, module: &str) -> LogLevel {
+        self.own_module_threshold(module)
+            .unwrap_or(self.default_module_threshold)
+    }
+
+    fn own_module_threshold(&self, module: &str) -> Option<LogLevel> {
+        let mut name = module;
+        loop {
+            if let Some(level) = self.module_thresholds.get(name) {
+                return Some(*level);
+            }
+            name = &name[..name.rfind('.')?];
+        }
+    }
+
+    /// Applies filter directives: `MODULE=level` entries set module
+    /// thresholds and a bare level becomes the default module threshold. The
+    /// console/file thresholds are lowered far enough for the most verbose
+    /// directive to get through, but never raised.
+    pub fn apply_env_filter(&mut self, filter: &EnvFilter) {
+        for (module, level) in &filter.modules {
+            self.set_module_threshold(module.clone(), *level);
+        }
+        if let Some(default) = filter.default {
+            self.set_default_module_threshold(default);
+        }
+
+        let Some(lowest) = filter.min_level() else {
+            return;
+        };
+        if lowest < self.console_threshold() {
+            self.set_console_threshold(lowest);
+        }
+        if lowest < self.file_threshold() {
+            self.set_file_threshold(lowest);
+        }
+    }
+
+    /// Applies the directives in the environment variable `var`, if set.
+    /// Invalid directives are reported and leave the configuration untouched.
+    pub fn apply_env(&mut self, var: &str) {
+        match EnvFilter::from_env(var) {
+            Some(Ok(filter)) => self.apply_env_filter(&filter),
+            Some(Err(error)) => self.outputs().report(error),
+            None => {}
+        }
+    }
+
+    pub fn set_console_max_level(&mut self, level: Option<LogLevel>) {
+        self.outputs().console.max_level = level;
+    }
+
+    pub fn set_file_max_level(&mut self, level: Option<LogLevel>) {
+        self.file_max_level = level;
+        if let Some(file) = &mut self.outputs().file {
+            file.max_level = level;
+        }
+    }
+
+    /// Takes effect the next time file logging is enabled.
+    pub fn set_file_config(&mut self, config: LogFileConfig) {
+        self.file_config = config;
+    }
+
+    pub fn file_config(&self) -> &LogFileConfig {
+        &self.file_config
+    }
+
+    pub fn set_file_rotation(&mut self, policy: Option<RotationPolicy>) {
+        self.file_rotation = policy.clone();
+        if let Some(file) = &mut self.outputs().file {
+            file.rotation = policy;
+        }
+    }
+
+    pub fn set_file_flush_policy(&mut self, policy: FlushPolicy) {
+        self.file_flush_policy = policy.clone();
+        if let Some(file) = &mut self.outputs().file {
+            file.flush_policy = policy;
+        }
+        self.refresh_flusher();
+    }
+
+    /// Runs the interval flusher while a file with a flush interval is open.
+    fn refresh_flusher(&mut self) {
+        let interval = self
+            .file_flush_policy
+            .interval
+            .filter(|_| self.file_logging_enabled());
+        if self.flusher.as_ref().map(|f| f.interval) != interval {
+            self.flusher = interval.map(|i| IntervalFlusher::start(i, self.outputs.clone()));
+        }
+    }
+
+    pub fn set_console_format(&mut self, format: LogFormat) {
+        self.outputs().console.format = format;
+    }
+
+    pub fn set_file_format(&mut self, format: LogFormat) {
+        self.file_format = format.clone();
+        if let Some(file) = &mut self.outputs().file {
+            file.format = format;
+        }
+    }
+
+    pub fn set_use_colors(&mut self, enabled: bool) {
+        self.outputs().console.use_colors = enabled;
+    }
+
+    pub fn set_use_icons_in_file(&mut self, enabled: bool) {
+        self.use_icons_in_file = enabled;
+        if let Some(file) = &mut self.outputs().file {
+            file.use_icons = enabled;
+        }
     }
 
+    pub fn file_logging_enabled(&self) -> bool {
+        self.outputs().file.is_some()
+    }
+
+    pub fn log_file_path(&self) -> Option<String> {
+        self.outputs().file.as_ref().map(|f| f.path().to_string())
+    }
+
+    pub fn try_enable_file_logging(&mut self) -> Result<(), LoggerError> {
+        if !self.file_logging_enabled() {
+            let file = self.file_config.open()?;
+            self.install_file(file);
+        }
+        Ok(())
+    }
+
+    fn install_file(&mut self, mut file: FileSink) {
+        file.threshold = self.file_threshold;
+        file.max_level = self.file_max_level;
+        file.rotation = self.file_rotation.clone();
+        file.use_icons = self.use_icons_in_file;
+        file.format = self.file_format.clone();
+        file.flush_policy = self.file_flush_policy.clone();
+        self.outputs().file = Some(file);
+        self.refresh_level_hint();
+        self.refresh_flusher();
+    }
+
+    /// Like `try_enable_file_logging`, but failures only go to the error
+    /// handler/counter and file logging stays disabled.
     pub fn enable_file_logging(&mut self) {
-        if !self.file_logging_enabled {
-            let filename = format!("log_{}.txt", chrono::Local::now().format("%Y%m%d_%H%M%S"));
-            self.log_file = Some(
-                std::fs::OpenOptions::new()
-                    .create(true)
-                    .truncate(true)
-                    .write(true)
-                    .open(&filename)
-                    .unwrap(),
-            );
-            self.log_file_path = Some(filename);
-            self.file_logging_enabled = true;
+        if let Err(error) = self.try_enable_file_logging() {
+            self.outputs().report(error);
+        }
+    }
+
+    /// Builds a standalone logger from a TOML file (see `LoggerConfig`).
+    /// To configure the global `LOGGER`, call `apply_config` on it instead.
+    pub fn from_config_file(path: impl AsRef<std::path::Path>) -> Result<Self, LoggerError> {
+        let config = LoggerConfig::load(path)?;
+        let mut logger = Self::new();
+        logger.apply_config(&config)?;
+        Ok(logger)
+    }
+
+    /// Replaces every setting described by `config`. The log file is reopened
+    /// only when its location changes or it was not open yet; it is opened
+    /// first, so on failure the logger keeps its previous settings.
+    pub fn apply_config(&mut self, config: &LoggerConfig) -> Result<(), LoggerError> {
+        let reopen = config.file_enabled
+            && (!self.file_logging_enabled() || self.file_config != config.file);
+        let file = if reopen {
+            // The new location may be the current file opened differently
+            self.flush();
+            Some(config.file.open()?)
+        } else {
+            None
+        };
+
+        self.set_include_date(config.include_date);
+        self.set_console_threshold(config.console_threshold);
+        self.set_console_max_level(config.console_max_level);
+        self.set_console_format(config.console_format.clone());
+        self.set_use_colors(config.use_colors);
+
+        self.set_file_threshold(config.file_threshold);
+        self.set_file_max_level(config.file_max_level);
+        self.set_file_format(config.file_format.clone());
+        self.set_use_icons_in_file(config.use_icons_in_file);
+        self.set_file_rotation(config.file_rotation.clone());
+        self.set_file_flush_policy(config.file_flush_policy.clone());
+        self.set_file_fallback(config.file_fallback);
+
+        self.module_thresholds = config.module_thresholds.iter().cloned().collect();
+        self.set_default_module_threshold(config.default_module_threshold);
+
+        match &config.async_writer {
+            Some(async_config) => self.enable_async(async_config.clone()),
+            None => self.disable_async(),
+        }
+
+        if !config.file_enabled || file.is_some() {
+            self.disable_file_logging();
         }
+        self.set_file_config(config.file.clone());
+        if let Some(file) = file {
+            self.install_file(file);
+        }
+        Ok(())
     }
 
     pub fn disable_file_logging(&mut self) {
-        self.log_file = None;
-        self.file_logging_enabled = false;
-        self.log_file_path = None;
+        // Queued records still belong in the file
+        if let Some(writer) = &self.writer {
+            writer.flush();
+        }
+        self.outputs().close_file();
+        self.refresh_level_hint();
+        self.refresh_flusher();
     }
 }
 
@@ -219,7 +795,72 @@ impl Default for Logger {
 
 // ---------- Global Logger ----------
 lazy_static::lazy_static! {
-    pub static ref LOGGER: Arc<Mutex<Logger>> = Arc::new(Mutex::new(Logger::new()));
+    static ref LEVEL_HINT: Arc<LevelHint> = Arc::new(LevelHint::new());
+    pub static ref LOGGER: Arc<Mutex<Logger>> =
+        Arc::new(Mutex::new(Logger::with_level_hint(LEVEL_HINT.clone())));
+}
+
+/// Most verbose level that can reach any output of the global `LOGGER`.
+/// Read without locking; the macros skip records below it.
+pub fn max_enabled_level() -> LogLevel {
+    level_from_hint(&LEVEL_HINT)
+}
+
+fn level_from_hint(hint: &LevelHint) -> LogLevel {
+    capped(LevelHint::load(&hint.level))
+}
+
+/// Raises a runtime level to the compile-time cap (`max_level_*` features).
+fn capped(runtime: LogLevel) -> LogLevel {
+    if runtime < STATIC_MAX_LEVEL {
+        STATIC_MAX_LEVEL
+    } else {
+        runtime
+    }
+}
+
+// ---------- Compile-time level cap ----------
+/// Most verbose level compiled into the binary, selected by the
+/// `max_level_*` / `release_max_level_*` cargo features. Macro calls below it
+/// are constant-folded away together with their arguments.
+pub const STATIC_MAX_LEVEL: LogLevel = static_max_level();
+
+const fn static_max_level() -> LogLevel {
+    // Within each group the most restrictive feature wins; in builds without
+    // debug assertions any `release_` feature replaces the `max_level_` one
+    if !cfg!(debug_assertions) {
+        if cfg!(feature = "release_max_level_fixed") {
+            return LogLevel::Fixed;
+        } else if cfg!(feature = "release_max_level_fatal") {
+            return LogLevel::Fatal;
+        } else if cfg!(feature = "release_max_level_error") {
+            return LogLevel::Error;
+        } else if cfg!(feature = "release_max_level_warning") {
+            return LogLevel::Warning;
+        } else if cfg!(feature = "release_max_level_info") {
+            return LogLevel::Info;
+        } else if cfg!(feature = "release_max_level_debug") {
+            return LogLevel::Debug;
+        } else if cfg!(feature = "release_max_level_verbose") {
+            return LogLevel::Verbose;
+        }
+    }
+
+    if cfg!(feature = "max_level_fixed") {
+        LogLevel::Fixed
+    } else if cfg!(feature = "max_level_fatal") {
+        LogLevel::Fatal
+    } else if cfg!(feature = "max_level_error") {
+        LogLevel::Error
+    } else if cfg!(feature = "max_level_warning") {
+        LogLevel::Warning
+    } else if cfg!(feature = "max_level_info") {
+        LogLevel::Info
+    } else if cfg!(feature = "max_level_debug") {
+        LogLevel::Debug
+    } else {
+        LogLevel::Verbose
+    }
 }
 
 // ---------- Type-safe Logger Macros ----------
@@ -387,6 +1028,16 @@ macro_rules! log_f64 {
     };
 }
 
+// Structured key/value field
+#[macro_export]
+macro_rules! log_kv {
+    ($k:expr, $v:expr) => {
+        |logger: &mut $crate::Logger| {
+            logger.append_kv($k, $v);
+        }
+    };
+}
+
 // Char
 #[macro_export]
 macro_rules! log_char {
@@ -401,12 +1052,97 @@ macro_rules! log_char {
 // ---------- Main print macro ----------
 #[macro_export]
 macro_rules! log_print {
+    (target: $target:expr, $level:expr, $($val:expr),+ $(,)?) => {{
+        let target: &$crate::LoggerHandle = $target;
+        let level = $level;
+        if level >= $crate::STATIC_MAX_LEVEL && level >= target.max_enabled_level() {
+            let mut logger = target.lock();
+            logger.set_level(level);
+            logger.set_location(file!(), line!());
+            $( $val(&mut logger); )+
+            target.print(&mut logger);
+        }
+    }};
     ($level:expr, $($val:expr),+ $(,)?) => {{
-        let mut logger = $crate::LOGGER.lock().unwrap();
-        logger.set_level($level);
-        $( $val(&mut logger); )+
-        logger.print();
+        let level = $level;
+        if level >= $crate::STATIC_MAX_LEVEL {
+            if let Some(target) = $crate::module_logger() {
+                $crate::log_print!(target: &target, level, $($val),+);
+            } else if level >= $crate::max_enabled_level() {
+                let mut logger = $crate::LOGGER.lock().unwrap();
+                logger.set_level(level);
+                logger.set_location(file!(), line!());
+                $( $val(&mut logger); )+
+                logger.print();
+            }
+        }
+    }};
+}
+
+// ---------- Format-string macros ----------
+#[macro_export]
+macro_rules! log_fmt {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let target: &$crate::LoggerHandle = $target;
+        let level = $level;
+        if level >= $crate::STATIC_MAX_LEVEL && level >= target.max_enabled_level() {
+            let mut logger = target.lock();
+            logger.set_level(level);
+            logger.set_location(file!(), line!());
+            logger.append_args(format_args!($($arg)+));
+            target.print(&mut logger);
+        }
     }};
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if level >= $crate::STATIC_MAX_LEVEL {
+            if let Some(target) = $crate::module_logger() {
+                $crate::log_fmt!(target: &target, level, $($arg)+);
+            } else if level >= $crate::max_enabled_level() {
+                let mut logger = $crate::LOGGER.lock().unwrap();
+                logger.set_level(level);
+                logger.set_location(file!(), line!());
+                logger.append_args(format_args!($($arg)+));
+                logger.print();
+            }
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! log_verbose {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Verbose, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Verbose, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_debug {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Debug, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Debug, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_info {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Info, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Info, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_warning {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Warning, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Warning, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_error {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Error, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Error, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_fatal {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Fatal, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Fatal, $($arg)+) };
+}
+#[macro_export]
+macro_rules! log_fixed {
+    (target: $target:expr, $($arg:tt)+) => { $crate::log_fmt!(target: $target, $crate::LogLevel::Fixed, $($arg)+) };
+    ($($arg:tt)+) => { $crate::log_fmt!($crate::LogLevel::Fixed, $($arg)+) };
 }
 
 // ---------- Init/Deinit ----------
@@ -416,9 +1152,10 @@ macro_rules! log_init {
         let mut logger = $crate::LOGGER.lock().unwrap();
         logger.set_console_threshold($console);
         logger.set_file_threshold($file);
-        logger.use_colors = $enable_colors;
-        logger.include_date = $include_date;
-        logger.use_icons_in_file = $use_icons;
+        logger.set_use_colors($enable_colors);
+        logger.set_include_date($include_date);
+        logger.set_use_icons_in_file($use_icons);
+        logger.apply_env($crate::ENV_FILTER_VAR);
         if $enable_file {
             logger.enable_file_logging();
         } else {
@@ -431,6 +1168,8 @@ macro_rules! log_init {
 macro_rules! log_deinit {
     () => {{
         let mut logger = $crate::LOGGER.lock().unwrap();
+        logger.disable_async();
+        logger.flush();
         logger.disable_file_logging();
     }};
 }
@@ -441,17 +1180,91 @@ mod tests {
     use super::*;
     use std::path::Path;
 
-    fn reset_logger() {
+    struct CaptureSink {
+        lines: Arc<Mutex<Vec<String>>>,
+        threshold: LogLevel,
+    }
+
+    impl Sink for CaptureSink {
+        fn write(&mut self, record: &Record) -> std::io::Result<()> {
+            self.lines.lock().unwrap().push(format!(
+                "{}|{}|{}",
+                record.level,
+                record.module,
+                record.text()
+            ));
+            Ok(())
+        }
+
+        fn threshold(&self) -> LogLevel {
+            self.threshold
+        }
+    }
+
+    fn capture_logger(threshold: LogLevel) -> (Logger, Arc<Mutex<Vec<String>>>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.add_sink(Box::new(CaptureSink {
+            lines: lines.clone(),
+            threshold,
+        }));
+        (logger, lines)
+    }
+
+    /// Fresh per-test directory under the system temp dir; names must be
+    /// unique across tests since they run in parallel.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ulogger_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Tests touching the global LOGGER run one at a time
+    static GLOBAL_LOGGER_LOCK: Mutex<()> = Mutex::new(());
+
+    fn reset_logger() -> std::sync::MutexGuard<'static, ()> {
+        let guard = GLOBAL_LOGGER_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
         let mut logger = LOGGER.lock().unwrap();
+        logger.disable_async();
         logger.disable_file_logging();
         logger.buffer.clear();
-        logger.console_threshold = LogLevel::Verbose;
-        logger.file_threshold = LogLevel::Verbose;
-        logger.use_colors = false;
-        logger.include_date = false;
-        logger.use_icons_in_file = false;
+        logger.clear_sinks();
+        logger.set_console_threshold(LogLevel::Verbose);
+        logger.set_file_threshold(LogLevel::Verbose);
+        logger.set_console_max_level(None);
+        logger.set_file_max_level(None);
+        logger.set_file_rotation(None);
+        // Keeps the timestamped default file name out of the working directory
+        logger.set_file_config(LogFileConfig {
+            directory: Some(temp_dir("global")),
+            ..Default::default()
+        });
+        logger.set_file_fallback(FileFallback::Discard);
+        logger.set_console_format(LogFormat::Text);
+        logger.set_file_format(LogFormat::Text);
+        logger.set_file_flush_policy(FlushPolicy::default());
+        logger.set_use_colors(false);
+        logger.set_include_date(false);
+        logger.set_use_icons_in_file(false);
+        logger.clear_module_thresholds();
+        logger.set_default_module_threshold(LogLevel::Verbose);
 
         LOG_MODULE.with(|m| *m.borrow_mut() = "");
+        guard
+    }
+
+    fn capture_global() -> Arc<Mutex<Vec<String>>> {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        LOGGER.lock().unwrap().add_sink(Box::new(CaptureSink {
+            lines: lines.clone(),
+            threshold: LogLevel::Verbose,
+        }));
+        lines
     }
 
     //
@@ -461,7 +1274,7 @@ mod tests {
     //
     #[test]
     fn test_basic_logging() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("TESTMOD");
 
         log_print!(
@@ -482,7 +1295,7 @@ mod tests {
     //
     #[test]
     fn test_hex_logging() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("HEXMOD");
 
         log_print!(
@@ -504,7 +1317,7 @@ mod tests {
     //
     #[test]
     fn test_pointer_logging() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("PTR");
 
         let x = 123u32;
@@ -521,7 +1334,7 @@ mod tests {
     //
     #[test]
     fn test_file_logging_creates_file() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("FILE");
 
         {
@@ -536,10 +1349,10 @@ mod tests {
         );
 
         let logger = LOGGER.lock().unwrap();
-        assert!(logger.file_logging_enabled);
+        assert!(logger.file_logging_enabled());
 
-        if let Some(path) = &logger.log_file_path {
-            assert!(Path::new(path).exists());
+        if let Some(path) = logger.log_file_path() {
+            assert!(Path::new(&path).exists());
             let _ = std::fs::remove_file(path);
         }
     }
@@ -551,7 +1364,7 @@ mod tests {
     //
     #[test]
     fn test_log_levels() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("LEVEL");
 
         {
@@ -573,8 +1386,9 @@ mod tests {
     // -----------------------------
     //
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_logging_all_types() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("ALLTYPES");
 
         let x = 123;
@@ -608,22 +1422,22 @@ mod tests {
     //
     #[test]
     fn test_file_logging_with_icons() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("ICONMOD");
 
         {
             let mut logger = LOGGER.lock().unwrap();
-            logger.use_icons_in_file = true;
+            logger.set_use_icons_in_file(true);
             logger.enable_file_logging();
         }
 
         log_print!(LogLevel::Warning, log_str!("Warning with icon"));
 
         let logger = LOGGER.lock().unwrap();
-        assert!(logger.file_logging_enabled);
+        assert!(logger.file_logging_enabled());
 
-        if let Some(path) = &logger.log_file_path {
-            assert!(Path::new(path).exists());
+        if let Some(path) = logger.log_file_path() {
+            assert!(Path::new(&path).exists());
             let _ = std::fs::remove_file(path);
         }
     }
@@ -635,7 +1449,7 @@ mod tests {
     //
     #[test]
     fn test_module_name_in_output_console() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("MODX");
 
         log_print!(LogLevel::Info, log_str!("Test123"));
@@ -652,8 +1466,9 @@ mod tests {
     // -----------------------------
     //
     #[test]
+    #[cfg_attr(ulogger_strips = "error", ignore = "stripped by the level cap")]
     fn test_module_name_in_file_output() {
-        reset_logger();
+        let _guard = reset_logger();
         log_module!("FILEMOD");
 
         {
@@ -666,7 +1481,7 @@ mod tests {
         let path;
         {
             let logger = LOGGER.lock().unwrap();
-            path = logger.log_file_path.clone();
+            path = logger.log_file_path();
         }
 
         assert!(path.is_some());
@@ -685,8 +1500,9 @@ mod tests {
     // -----------------------------
     //
     #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
     fn test_module_empty_when_not_set() {
-        reset_logger();
+        let _guard = reset_logger();
 
         {
             let mut logger = LOGGER.lock().unwrap();
@@ -695,7 +1511,7 @@ mod tests {
 
         log_print!(LogLevel::Info, log_str!("NoModuleTest"));
 
-        let path = LOGGER.lock().unwrap().log_file_path.clone().unwrap();
+        let path = LOGGER.lock().unwrap().log_file_path().unwrap();
         let content = std::fs::read_to_string(&path).unwrap();
 
         assert!(!content.contains(" |  | ")); // No double pipes
@@ -703,4 +1519,1563 @@ mod tests {
 
         let _ = std::fs::remove_file(path);
     }
+
+    //
+    // -----------------------------
+    //  Custom sinks receive records above their threshold
+    // -----------------------------
+    //
+    #[test]
+    fn test_custom_sink() {
+        let (mut logger, lines) = capture_logger(LogLevel::Warning);
+
+        logger.set_level(LogLevel::Info);
+        logger.append("dropped");
+        logger.print();
+
+        logger.set_level(LogLevel::Error);
+        logger.append("kept");
+        logger.append(7);
+        logger.print();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0], "  ERROR||kept 7 ");
+    }
+
+    //
+    // -----------------------------
+    //  File sink honors its min/max level range
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_sink_level_range() {
+        let dir = temp_dir("range");
+        let path = dir.join("app.txt");
+        let mut file = FileSink::create(path.to_string_lossy()).unwrap();
+        file.threshold = LogLevel::Warning;
+        file.max_level = Some(LogLevel::Fatal);
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.add_sink(Box::new(file));
+
+        for (level, text) in [
+            (LogLevel::Info, "below"),
+            (LogLevel::Warning, "lower-bound"),
+            (LogLevel::Fatal, "upper-bound"),
+            (LogLevel::Fixed, "above"),
+        ] {
+            logger.set_level(level);
+            logger.append(text);
+            logger.print();
+        }
+        logger.flush();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("below"));
+        assert!(content.contains("lower-bound"));
+        assert!(content.contains("upper-bound"));
+        assert!(!content.contains("above"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  set_file_threshold filters the built-in file output
+    // -----------------------------
+    //
+    #[test]
+    fn test_set_file_threshold() {
+        let dir = temp_dir("file_threshold");
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.txt"),
+            append: false,
+        });
+        logger.set_file_threshold(LogLevel::Warning);
+        logger.enable_file_logging();
+        assert_eq!(logger.file_threshold(), LogLevel::Warning);
+
+        for (level, text) in [(LogLevel::Info, "dropped"), (LogLevel::Warning, "kept")] {
+            logger.set_level(level);
+            logger.append(text);
+            logger.print();
+        }
+
+        // Changing the threshold also applies to the open file
+        logger.set_file_threshold(LogLevel::Error);
+        logger.set_level(LogLevel::Warning);
+        logger.append("dropped-later");
+        logger.print();
+        logger.flush();
+
+        let content = std::fs::read_to_string(logger.log_file_path().unwrap()).unwrap();
+        assert!(!content.contains("dropped"));
+        assert!(content.contains("kept"));
+
+        logger.disable_file_logging();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  Size-based rotation keeps at most max_files segments
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_rotation_by_size() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("app.txt");
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.txt"),
+            append: false,
+        });
+        logger.set_file_rotation(Some(RotationPolicy {
+            max_bytes: Some(64),
+            max_files: Some(2),
+            ..Default::default()
+        }));
+        logger.enable_file_logging();
+
+        for i in 0..5 {
+            logger.set_level(LogLevel::Info);
+            logger.append("record number");
+            logger.append(i);
+            logger.print();
+        }
+        logger.flush();
+
+        assert!(path.exists());
+        assert!(dir.join("app.1.txt").exists());
+        assert!(dir.join("app.2.txt").exists());
+        assert!(!dir.join("app.3.txt").exists());
+
+        // The reported path keeps pointing at the active file
+        let active = logger.log_file_path().unwrap();
+        assert_eq!(Path::new(&active), path);
+        let newest = std::fs::read_to_string(&active).unwrap();
+        assert!(newest.contains("record number 4"));
+
+        logger.disable_file_logging();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_file_rotation_dated_keeps_other_files() {
+        let dir = temp_dir("rotate_dated");
+        // Another subsystem's log sharing the stem is not a segment
+        std::fs::write(dir.join("app.net.log"), "other log").unwrap();
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.log"),
+            append: false,
+        });
+        logger.set_file_rotation(Some(RotationPolicy {
+            max_bytes: Some(64),
+            naming: RotationNaming::Dated,
+            max_files: Some(1),
+            ..Default::default()
+        }));
+        logger.enable_file_logging();
+
+        for i in 0..4 {
+            logger.set_level(LogLevel::Info);
+            logger.append("record number");
+            logger.append(i);
+            logger.print();
+        }
+        logger.disable_file_logging();
+
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        // One dated segment (`app.<stamp>[.<n>].log`), the active file and the other log
+        assert_eq!(names.len(), 3, "{:?}", names);
+        assert!(names[0].starts_with("app.20"), "{:?}", names);
+        assert_eq!(names[1..], ["app.log", "app.net.log"]);
+        assert_eq!(logger.error_count(), 0);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("app.net.log")).unwrap(),
+            "other log"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  Rotated segments are gzipped in the background
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_rotation_compression() {
+        use std::io::Read;
+
+        let dir = temp_dir("gzip");
+        let path = dir.join("app.txt");
+
+        let mut file = FileSink::create(path.to_string_lossy()).unwrap();
+        file.rotation = Some(RotationPolicy {
+            max_bytes: Some(64),
+            compress: true,
+            ..Default::default()
+        });
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.add_sink(Box::new(file));
+
+        for i in 0..3 {
+            logger.set_level(LogLevel::Info);
+            logger.append("compressed record");
+            logger.append(i);
+            logger.print();
+        }
+        // Dropping the sink waits for the compressor thread
+        drop(logger);
+
+        assert!(!dir.join("app.1.txt").exists());
+        let gz = std::fs::File::open(dir.join("app.2.txt.gz")).unwrap();
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(gz)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert!(content.contains("compressed record 0"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_file_rotation_total_size_keeps_other_files() {
+        let dir = temp_dir("rotate_total");
+        for other in ["app.old.txt", "app.net.txt"] {
+            std::fs::write(dir.join(other), "not a segment").unwrap();
+        }
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.txt"),
+            append: false,
+        });
+        logger.set_file_rotation(Some(RotationPolicy {
+            max_bytes: Some(64),
+            max_total_bytes: Some(1),
+            ..Default::default()
+        }));
+        logger.enable_file_logging();
+
+        for i in 0..3 {
+            logger.set_level(LogLevel::Info);
+            logger.append("pruned record");
+            logger.append(i);
+            logger.print();
+        }
+        logger.disable_file_logging();
+
+        assert_eq!(logger.error_count(), 0);
+        assert!(!dir.join("app.1.txt").exists());
+        assert!(!dir.join("app.2.txt").exists());
+        for other in ["app.old.txt", "app.net.txt"] {
+            assert!(dir.join(other).exists(), "{} was pruned", other);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  Background rotation failures are reported
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_rotation_background_errors() {
+        let dir = temp_dir("gzip_err");
+        // Named like an old numbered segment, but cannot be removed as a file
+        std::fs::create_dir_all(dir.join("app.3.txt")).unwrap();
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.txt"),
+            append: false,
+        });
+        logger.set_file_rotation(Some(RotationPolicy {
+            max_bytes: Some(64),
+            max_total_bytes: Some(1),
+            compress: true,
+            ..Default::default()
+        }));
+        logger.enable_file_logging();
+
+        for i in 0..2 {
+            logger.set_level(LogLevel::Info);
+            logger.append("pruned record");
+            logger.append(i);
+            logger.print();
+        }
+
+        // Reported once: by the next write if it already finished, else by the flush
+        logger.flush();
+        assert_eq!(logger.error_count(), 1);
+
+        logger.disable_file_logging();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  File location: directory, pattern and append mode
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_config_directory_pattern_append() {
+        let dir = temp_dir("file_config").join("nested");
+        let config = LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app_{pid}_%Y.log"),
+            append: true,
+        };
+
+        let expected = dir.join(format!(
+            "app_{}_{}.log",
+            std::process::id(),
+            Local::now().format("%Y")
+        ));
+        assert_eq!(config.resolve_path().unwrap(), expected);
+
+        for text in ["first run", "second run"] {
+            let mut logger = Logger::new();
+            logger.set_console_threshold(LogLevel::Fixed);
+            logger.set_file_config(config.clone());
+            logger.enable_file_logging();
+            assert_eq!(
+                logger.log_file_path(),
+                Some(expected.to_string_lossy().into_owned())
+            );
+
+            logger.set_level(LogLevel::Info);
+            logger.append(text);
+            logger.print();
+            logger.disable_file_logging();
+        }
+
+        let content = std::fs::read_to_string(&expected).unwrap();
+        assert!(content.contains("first run"));
+        assert!(content.contains("second run"));
+
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    //
+    // -----------------------------
+    //  File open failures are returned instead of panicking
+    // -----------------------------
+    //
+    #[test]
+    fn test_enable_file_logging_failure() {
+        let dir = temp_dir("blocker");
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "not a directory").unwrap();
+
+        let mut logger = Logger::new();
+        logger.set_file_config(LogFileConfig {
+            directory: Some(blocker.join("logs")),
+            ..Default::default()
+        });
+
+        let result = logger.try_enable_file_logging();
+        assert!(matches!(result, Err(LoggerError::OpenFile { .. })));
+        assert!(!logger.file_logging_enabled());
+
+        logger.enable_file_logging();
+        assert!(!logger.file_logging_enabled());
+        assert_eq!(logger.error_count(), 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  Sink write failures reach the error handler
+    // -----------------------------
+    //
+    #[test]
+    fn test_sink_write_errors_are_reported() {
+        struct BrokenSink;
+
+        impl Sink for BrokenSink {
+            fn write(&mut self, _: &Record) -> std::io::Result<()> {
+                Err(std::io::Error::other("disk full"))
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_handler = seen.clone();
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.add_sink(Box::new(BrokenSink));
+        logger.set_error_handler(Some(Box::new(move |error| {
+            seen_by_handler.lock().unwrap().push(error.to_string());
+        })));
+
+        for _ in 0..2 {
+            logger.set_level(LogLevel::Info);
+            logger.append("lost");
+            logger.print();
+        }
+
+        assert_eq!(logger.error_count(), 2);
+        assert_eq!(seen.lock().unwrap()[0], "sink write failed: disk full");
+    }
+
+    //
+    // -----------------------------
+    //  Format-string macros
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "debug", ignore = "stripped by the level cap")]
+    fn test_format_string_macros() {
+        let _guard = reset_logger();
+        let lines = capture_global();
+        log_module!("FMT");
+
+        {
+            let mut logger = LOGGER.lock().unwrap();
+            logger.set_console_threshold(LogLevel::Fixed);
+        }
+
+        let host = "example.org";
+        log_info!("connected to {} in {}ms", host, 12);
+        log_error!("code={code:04}", code = 7);
+        log_debug!("plain text");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            [
+                "   INFO|FMT|connected to example.org in 12ms",
+                "  ERROR|FMT|code=0007",
+                "  DEBUG|FMT|plain text",
+            ]
+        );
+    }
+
+    //
+    // -----------------------------
+    //  `log` crate records go through the global logger
+    // -----------------------------
+    //
+    #[cfg(feature = "log")]
+    #[test]
+    #[cfg_attr(ulogger_strips = "verbose", ignore = "stripped by the level cap")]
+    fn test_log_bridge() {
+        use log::Log;
+
+        let _guard = reset_logger();
+        let lines = capture_global();
+        LOGGER
+            .lock()
+            .unwrap()
+            .set_console_threshold(LogLevel::Fixed);
+
+        let record = |level, target| {
+            log::Record::builder()
+                .args(format_args!("from log"))
+                .level(level)
+                .target(target)
+                .build()
+        };
+
+        LogBridge.log(&record(log::Level::Warn, "hyper::client"));
+        log_module!("MINE");
+        LogBridge.log(&record(log::Level::Trace, "hyper::client"));
+
+        // Targets pick registered loggers, with `::` read as `.`
+        let (hyper, hyper_lines) = capture_logger(LogLevel::Verbose);
+        register_logger("hyper", hyper);
+        log_module!("");
+        LogBridge.log(&record(log::Level::Debug, "hyper::client"));
+        unregister_logger("hyper");
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["WARNING|hyper::client|from log", "VERBOSE|MINE|from log"]
+        );
+        assert_eq!(
+            *hyper_lines.lock().unwrap(),
+            ["  DEBUG|hyper::client|from log"]
+        );
+    }
+
+    //
+    // -----------------------------
+    //  tracing events rendered through the global logger
+    // -----------------------------
+    //
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[cfg_attr(ulogger_strips = "warning", ignore = "stripped by the level cap")]
+    fn test_tracing_layer() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _guard = reset_logger();
+        let lines = capture_global();
+        LOGGER
+            .lock()
+            .unwrap()
+            .set_console_threshold(LogLevel::Fixed);
+
+        let subscriber = tracing_subscriber::registry().with(UloggerLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("net");
+            let _outer = span.enter();
+            let span = tracing::info_span!("tls");
+            let _inner = span.enter();
+            tracing::warn!(user_id = 42, peer = "10.0.0.1", "handshake failed");
+        });
+
+        let (db, db_lines) = capture_logger(LogLevel::Verbose);
+        register_logger("db", db);
+        let subscriber = tracing_subscriber::registry().with(UloggerLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "db::pool", "exhausted");
+        });
+        unregister_logger("db");
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["WARNING|net:tls|handshake failed user_id=42 peer=10.0.0.1"]
+        );
+        assert_eq!(*db_lines.lock().unwrap(), ["WARNING|db::pool|exhausted"]);
+    }
+
+    //
+    // -----------------------------
+    //  Structured fields are kept apart from the message
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
+    fn test_structured_fields() {
+        struct FieldSink(Arc<Mutex<Vec<(String, FieldValue)>>>);
+
+        impl Sink for FieldSink {
+            fn write(&mut self, record: &Record) -> std::io::Result<()> {
+                assert_eq!(record.message, "login ");
+                self.0.lock().unwrap().extend_from_slice(record.fields);
+                Ok(())
+            }
+        }
+
+        let _guard = reset_logger();
+        let (fields, lines) = (Arc::new(Mutex::new(Vec::new())), capture_global());
+        {
+            let mut logger = LOGGER.lock().unwrap();
+            logger.set_console_threshold(LogLevel::Fixed);
+            logger.add_sink(Box::new(FieldSink(fields.clone())));
+        }
+
+        log_print!(
+            LogLevel::Info,
+            log_str!("login"),
+            log_kv!("user_id", 42),
+            log_kv!("name", "John Doe"),
+            log_kv!("admin", false)
+        );
+
+        assert_eq!(
+            *fields.lock().unwrap(),
+            [
+                (String::from("user_id"), FieldValue::I64(42)),
+                (String::from("name"), FieldValue::from("John Doe")),
+                (String::from("admin"), FieldValue::Bool(false)),
+            ]
+        );
+        assert_eq!(
+            lines.lock().unwrap()[0],
+            "   INFO||login user_id=42 name=\"John Doe\" admin=false"
+        );
+    }
+
+    //
+    // -----------------------------
+    //  JSON Lines file output
+    // -----------------------------
+    //
+    #[test]
+    fn test_json_lines_file_output() {
+        let dir = temp_dir("json");
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_format(LogFormat::Json);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.jsonl"),
+            append: false,
+        });
+        logger.try_enable_file_logging().unwrap();
+
+        std::thread::Builder::new()
+            .name(String::from("worker"))
+            .spawn(move || {
+                logger.set_level(LogLevel::Warning);
+                logger.append("disk \"sda\" at");
+                logger.append_kv("percent", 97);
+                logger.append_kv("mount", "/var\nlog");
+                logger.print_with_module("DISK");
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let content = std::fs::read_to_string(dir.join("app.jsonl")).unwrap();
+        let line = content.lines().next().unwrap();
+        assert!(line.starts_with("{\"timestamp\":\""));
+        assert!(line.ends_with(
+            "\"level\":\"WARNING\",\"module\":\"DISK\",\"message\":\"disk \\\"sda\\\" at\",\
+             \"thread\":\"worker\",\"fields\":{\"percent\":97,\"mount\":\"/var\\nlog\"}}"
+        ));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  logfmt quoting and escaping
+    // -----------------------------
+    //
+    #[test]
+    fn test_logfmt_format() {
+        let time = Local::now();
+        let fields = [
+            (String::from("user"), FieldValue::from("john")),
+            (String::from("query"), FieldValue::from("a=\"b\"\nc")),
+            (String::from("empty"), FieldValue::from("")),
+            (String::from("ms"), FieldValue::F64(1.5)),
+        ];
+        let record = Record {
+            level: LogLevel::Info,
+            time,
+            timestamp: "",
+            module: "NET",
+            thread: "main",
+            location: None,
+            message: "request done ",
+            fields: &fields,
+        };
+
+        let line = LogFormat::Logfmt.render(&record).unwrap();
+        let expected = format!(
+            "ts={} level=info module=NET msg=\"request done\" user=john \
+             query=\"a=\\\"b\\\"\\nc\" empty=\"\" ms=1.5\n",
+            time.to_rfc3339_opts(chrono::SecondsFormat::Micros, false)
+        );
+        assert_eq!(line, expected);
+        assert_eq!(LogFormat::Text.render(&record), None);
+    }
+
+    //
+    // -----------------------------
+    //  Line patterns: tokens, alignment and truncation
+    // -----------------------------
+    //
+    #[test]
+    fn test_line_pattern() {
+        let pattern =
+            LinePattern::compile("%d{%H:%M:%S} [%l{:>7}] %m{:<6.4}|%t %s: %msg 100%%").unwrap();
+
+        let time = Local::now();
+        let fields = [(String::from("id"), FieldValue::U64(3))];
+        let record = Record {
+            level: LogLevel::Info,
+            time,
+            timestamp: "",
+            module: "NETWORK",
+            thread: "main",
+            location: Some(("src/net.rs", 42)),
+            message: "connected ",
+            fields: &fields,
+        };
+
+        assert_eq!(
+            LogFormat::Pattern(pattern).render(&record).unwrap(),
+            format!(
+                "{} [   INFO] NETW  |main src/net.rs:42: connected id=3 100%\n",
+                time.format("%H:%M:%S")
+            )
+        );
+
+        for bad in ["%q", "%m{:<x}", "trailing %", "%d{%Q}"] {
+            assert!(matches!(
+                LinePattern::compile(bad),
+                Err(LoggerError::InvalidLinePattern { .. })
+            ));
+        }
+    }
+
+    //
+    // -----------------------------
+    //  Macros record their call site
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "warning", ignore = "stripped by the level cap")]
+    fn test_macros_record_source_location() {
+        struct LocationSink(Arc<Mutex<Vec<String>>>);
+
+        impl Sink for LocationSink {
+            fn write(&mut self, record: &Record) -> std::io::Result<()> {
+                if let Some((file, line)) = record.location {
+                    self.0.lock().unwrap().push(format!("{}:{}", file, line));
+                }
+                Ok(())
+            }
+        }
+
+        let _guard = reset_logger();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        {
+            let mut logger = LOGGER.lock().unwrap();
+            logger.set_console_threshold(LogLevel::Fixed);
+            logger.add_sink(Box::new(LocationSink(seen.clone())));
+        }
+
+        let line = line!() + 1;
+        log_warning!("here");
+
+        assert_eq!(*seen.lock().unwrap(), [format!("{}:{}", file!(), line)]);
+    }
+
+    //
+    // -----------------------------
+    //  Async writer keeps order and call-site thread names
+    // -----------------------------
+    //
+    #[test]
+    fn test_async_writer_flush() {
+        let (mut logger, lines) = capture_logger(LogLevel::Verbose);
+        logger.enable_async(AsyncConfig::default());
+
+        for i in 0..100 {
+            logger.set_level(LogLevel::Debug);
+            logger.append(i);
+            logger.print_with_module("ASYNC");
+        }
+        logger.flush();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 100);
+        assert_eq!(lines[0], "  DEBUG|ASYNC|0 ");
+        assert_eq!(lines[99], "  DEBUG|ASYNC|99 ");
+    }
+
+    //
+    // -----------------------------
+    //  Async overflow policies
+    // -----------------------------
+    //
+    #[test]
+    fn test_async_overflow_policies() {
+        use std::sync::mpsc;
+
+        // Holds the writer thread inside the first write until released
+        struct GateSink {
+            lines: Arc<Mutex<Vec<String>>>,
+            entered: mpsc::Sender<()>,
+            release: mpsc::Receiver<()>,
+        }
+
+        impl Sink for GateSink {
+            fn write(&mut self, record: &Record) -> std::io::Result<()> {
+                if self.lines.lock().unwrap().is_empty() {
+                    let _ = self.entered.send(());
+                    let _ = self.release.recv();
+                }
+                self.lines.lock().unwrap().push(record.message.to_string());
+                Ok(())
+            }
+        }
+
+        for (overflow, expected) in [
+            (OverflowPolicy::DropNewest, ["0 ", "1 ", "2 "]),
+            (OverflowPolicy::DropOldest, ["0 ", "3 ", "4 "]),
+        ] {
+            let lines = Arc::new(Mutex::new(Vec::new()));
+            let (entered_tx, entered) = mpsc::channel();
+            let (release, release_rx) = mpsc::channel();
+
+            let mut logger = Logger::new();
+            logger.set_console_threshold(LogLevel::Fixed);
+            logger.add_sink(Box::new(GateSink {
+                lines: lines.clone(),
+                entered: entered_tx,
+                release: release_rx,
+            }));
+            logger.enable_async(AsyncConfig {
+                capacity: 2,
+                overflow,
+            });
+
+            for i in 0..5 {
+                logger.set_level(LogLevel::Info);
+                logger.append(i);
+                logger.print();
+                if i == 0 {
+                    entered.recv().unwrap();
+                }
+            }
+
+            release.send(()).unwrap();
+            logger.flush();
+
+            assert_eq!(*lines.lock().unwrap(), expected);
+            assert_eq!(logger.dropped_count(), 2);
+        }
+    }
+
+    //
+    // -----------------------------
+    //  Buffered file output follows its flush policy
+    // -----------------------------
+    //
+    #[test]
+    fn test_file_flush_policy() {
+        let dir = temp_dir("flush");
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Fixed);
+        logger.set_file_flush_policy(FlushPolicy {
+            every_records: Some(3),
+            immediate_level: Some(LogLevel::Error),
+            ..Default::default()
+        });
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.log"),
+            append: false,
+        });
+        logger.try_enable_file_logging().unwrap();
+
+        let path = dir.join("app.log");
+        let mut log = |level, text| {
+            logger.set_level(level);
+            logger.append(text);
+            logger.print();
+            std::fs::read_to_string(&path).unwrap()
+        };
+
+        assert!(log(LogLevel::Info, "one").is_empty());
+        assert!(log(LogLevel::Info, "two").is_empty());
+        assert!(log(LogLevel::Info, "three").contains("three"));
+        assert!(log(LogLevel::Info, "four").ends_with("three \n"));
+        assert!(log(LogLevel::Error, "five").contains("four"));
+
+        // The interval is honored without further records
+        logger.set_file_flush_policy(FlushPolicy {
+            every_records: None,
+            interval: Some(std::time::Duration::from_millis(50)),
+            ..Default::default()
+        });
+        logger.set_level(LogLevel::Info);
+        logger.append("six");
+        logger.print();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("six"));
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !std::fs::read_to_string(&path).unwrap().contains("six") {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "interval flush missed"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        logger.disable_file_logging();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    //
+    // -----------------------------
+    //  Disabled levels skip the lock and the arguments
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "verbose", ignore = "stripped by the level cap")]
+    fn test_max_enabled_level_fast_path() {
+        let _guard = reset_logger();
+        assert_eq!(max_enabled_level(), LogLevel::Verbose);
+
+        LOGGER
+            .lock()
+            .unwrap()
+            .set_console_threshold(LogLevel::Warning);
+        assert_eq!(max_enabled_level(), LogLevel::Warning);
+
+        let evaluated = std::cell::Cell::new(false);
+        log_print!(LogLevel::Debug, |_: &mut Logger| evaluated.set(true));
+        log_debug!("{}", {
+            evaluated.set(true);
+            1
+        });
+        assert!(!evaluated.get());
+
+        log_print!(LogLevel::Error, |_: &mut Logger| evaluated.set(true));
+        assert!(evaluated.get());
+
+        let lines = capture_global();
+        assert_eq!(max_enabled_level(), LogLevel::Verbose);
+        log_debug!("now enabled");
+        assert_eq!(lines.lock().unwrap().len(), 1);
+    }
+
+    //
+    // -----------------------------
+    //  Compile-time cap (run with e.g. `--features max_level_info`)
+    // -----------------------------
+    //
+    #[cfg(any(
+        feature = "max_level_debug",
+        feature = "max_level_info",
+        feature = "max_level_warning",
+        feature = "max_level_error",
+        feature = "max_level_fatal",
+        feature = "max_level_fixed"
+    ))]
+    #[test]
+    fn test_static_max_level() {
+        // A `release_max_level_verbose` feature can lift the cap again
+        if STATIC_MAX_LEVEL == LogLevel::Verbose {
+            return;
+        }
+        let _guard = reset_logger();
+        let lines = capture_global();
+        assert_eq!(max_enabled_level(), STATIC_MAX_LEVEL);
+
+        let below = LogLevel::ALL[STATIC_MAX_LEVEL as usize - 1];
+        let evaluated = std::cell::Cell::new(false);
+        log_print!(below, |_: &mut Logger| evaluated.set(true));
+        log_fmt!(below, "stripped");
+        log_fmt!(STATIC_MAX_LEVEL, "kept");
+
+        assert!(!evaluated.get());
+        assert_eq!(
+            *lines.lock().unwrap(),
+            [format!("{}||kept", STATIC_MAX_LEVEL)]
+        );
+    }
+
+    //
+    // -----------------------------
+    //  Per-module thresholds
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "verbose", ignore = "stripped by the level cap")]
+    fn test_module_thresholds() {
+        let _guard = reset_logger();
+        let lines = capture_global();
+        {
+            let mut logger = LOGGER.lock().unwrap();
+            logger.set_console_threshold(LogLevel::Fixed);
+            logger.set_default_module_threshold(LogLevel::Warning);
+            logger.set_module_threshold("NET", LogLevel::Verbose);
+        }
+        assert_eq!(max_enabled_level(), LogLevel::Verbose);
+
+        log_module!("NET");
+        log_verbose!("packet");
+        log_module!("DB");
+        log_info!("query");
+        log_warning!("slow");
+
+        LOGGER
+            .lock()
+            .unwrap()
+            .set_module_threshold("NET", LogLevel::Error);
+        assert_eq!(max_enabled_level(), LogLevel::Warning);
+        log_module!("NET");
+        log_warning!("retry");
+        log_error!("down");
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["VERBOSE|NET|packet", "WARNING|DB|slow", "  ERROR|NET|down"]
+        );
+    }
+
+    //
+    // -----------------------------
+    //  Environment filter directives
+    // -----------------------------
+    //
+    #[test]
+    fn test_env_filter_directives() {
+        let filter = EnvFilter::parse(" warning, NET=Verbose ,DB=error,").unwrap();
+        assert_eq!(filter.default, Some(LogLevel::Warning));
+        assert_eq!(
+            filter.modules,
+            [
+                (String::from("NET"), LogLevel::Verbose),
+                (String::from("DB"), LogLevel::Error)
+            ]
+        );
+        assert!(matches!(
+            EnvFilter::parse("info,NET=loud"),
+            Err(LoggerError::InvalidDirective(d)) if d == "NET=loud"
+        ));
+
+        let (mut logger, lines) = capture_logger(LogLevel::Verbose);
+        logger.apply_env_filter(&filter);
+        assert_eq!(logger.module_threshold("APP"), LogLevel::Warning);
+        for (module, level) in [
+            ("NET", LogLevel::Verbose),
+            ("DB", LogLevel::Warning),
+            ("DB", LogLevel::Error),
+            ("APP", LogLevel::Info),
+            ("APP", LogLevel::Warning),
+        ] {
+            logger.set_level(level);
+            logger.append("msg");
+            logger.print_with_module(module);
+        }
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["VERBOSE|NET|msg ", "  ERROR|DB|msg ", "WARNING|APP|msg "]
+        );
+
+        std::env::set_var("ULOGGER_TEST_INVALID", "=debug");
+        logger.apply_env("ULOGGER_TEST_INVALID");
+        assert_eq!(logger.error_count(), 1);
+        assert_eq!(logger.module_threshold("APP"), LogLevel::Warning);
+    }
+
+    #[test]
+    fn test_env_filter_keeps_output_thresholds() {
+        let dir = temp_dir("env_filter");
+
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Error);
+        logger.set_file_config(LogFileConfig {
+            directory: Some(dir.clone()),
+            pattern: String::from("app.txt"),
+            append: false,
+        });
+        logger.set_file_threshold(LogLevel::Verbose);
+        logger.enable_file_logging();
+
+        // Module-only directives keep the default and lower the console
+        logger.apply_env_filter(&EnvFilter::parse("NET=verbose,DB=error").unwrap());
+        assert_eq!(logger.console_threshold(), LogLevel::Verbose);
+        assert_eq!(logger.file_threshold(), LogLevel::Verbose);
+        assert_eq!(logger.module_threshold("APP"), LogLevel::Verbose);
+        logger.set_console_threshold(LogLevel::Fixed);
+        for (module, level) in [
+            ("APP", LogLevel::Info),
+            ("NET", LogLevel::Verbose),
+            ("DB", LogLevel::Warning),
+        ] {
+            logger.set_level(level);
+            logger.append("msg");
+            logger.print_with_module(module);
+        }
+
+        // A bare level only lowers the outputs, never raises them
+        logger.apply_env_filter(&EnvFilter::parse("warning,NET=debug").unwrap());
+        assert_eq!(logger.console_threshold(), LogLevel::Debug);
+        assert_eq!(logger.file_threshold(), LogLevel::Verbose);
+        assert_eq!(logger.module_threshold("APP"), LogLevel::Warning);
+
+        let path = logger.log_file_path().unwrap();
+        logger.disable_file_logging();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("INFO | APP"), "{}", content);
+        assert!(content.contains("VERBOSE | NET"), "{}", content);
+        assert!(!content.contains("| DB"), "{}", content);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Raising one module's verbosity opens outputs set up above it
+        let mut logger = Logger::new();
+        logger.set_console_threshold(LogLevel::Warning);
+        logger.set_file_threshold(LogLevel::Warning);
+        logger.apply_env_filter(&EnvFilter::parse("NET=verbose").unwrap());
+        assert_eq!(logger.console_threshold(), LogLevel::Verbose);
+        assert_eq!(logger.file_threshold(), LogLevel::Verbose);
+        assert_eq!(
+            LoggerHandle::from(logger).max_enabled_level(),
+            LogLevel::Verbose.max(STATIC_MAX_LEVEL)
+        );
+    }
+
+    //
+    // -----------------------------
+    //  Level names and conversions
+    // -----------------------------
+    //
+    #[test]
+    fn test_level_parsing() {
+        for level in LogLevel::ALL {
+            assert_eq!(level.as_str().parse::<LogLevel>(), Ok(level));
+            assert_eq!(LogLevel::try_from(u8::from(level)), Ok(level));
+        }
+        assert_eq!(LogLevel::Debug.to_string(), "  DEBUG");
+        assert_eq!(LogLevel::Debug.as_str(), "DEBUG");
+
+        assert_eq!("warn".parse(), Ok(LogLevel::Warning));
+        assert_eq!(" Err ".parse(), Ok(LogLevel::Error));
+        assert_eq!("v".parse(), Ok(LogLevel::Verbose));
+        assert_eq!("trace".parse(), Ok(LogLevel::Verbose));
+        assert_eq!("fix".parse(), Ok(LogLevel::Fixed));
+        assert!("f".parse::<LogLevel>().is_err());
+        assert!("".parse::<LogLevel>().is_err());
+        assert_eq!(
+            "loud".parse::<LogLevel>().unwrap_err().to_string(),
+            "unknown log level 'loud'"
+        );
+        assert!(LogLevel::try_from(7).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_level_serde() {
+        use serde::de::value::{Error, StrDeserializer, U64Deserializer};
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        let name: StrDeserializer<Error> = "warn".into_deserializer();
+        assert_eq!(LogLevel::deserialize(name), Ok(LogLevel::Warning));
+        let number: U64Deserializer<Error> = 4u64.into_deserializer();
+        assert_eq!(LogLevel::deserialize(number), Ok(LogLevel::Error));
+        let bad: StrDeserializer<Error> = "loud".into_deserializer();
+        assert!(LogLevel::deserialize(bad).is_err());
+
+        // A non-self-describing format only supports the typed calls
+        struct Compact(&'static str);
+        impl<'de> serde::Deserializer<'de> for Compact {
+            type Error = Error;
+
+            fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+                Err(serde::de::Error::custom("deserialize_any is not supported"))
+            }
+
+            fn deserialize_str<V: serde::de::Visitor<'de>>(
+                self,
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                visitor.visit_str(self.0)
+            }
+
+            fn is_human_readable(&self) -> bool {
+                false
+            }
+
+            serde::forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char string
+                bytes byte_buf option unit unit_struct newtype_struct seq tuple
+                tuple_struct map struct enum identifier ignored_any
+            }
+        }
+        assert_eq!(
+            LogLevel::deserialize(Compact(LogLevel::Fatal.as_str())),
+            Ok(LogLevel::Fatal)
+        );
+    }
+
+    //
+    // -----------------------------
+    //  TOML configuration file
+    // -----------------------------
+    //
+    #[test]
+    fn test_config_file() {
+        let dir = temp_dir("config_file");
+        let path = dir.join("ulogger.toml");
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+include_date = false
+default_level = "warn"
+
+[console]
+threshold = "fixed"
+colors = false
+
+[file]
+enabled = true
+directory = "{}"
+pattern = "app.log"
+threshold = "debug"
+format = "pattern"
+line_pattern = "%l|%m|%msg"
+
+[file.rotation]
+max_bytes = 4096
+naming = "dated"
+
+[modules]
+NET = "verbose"
+"#,
+                dir.display()
+            ),
+        )
+        .unwrap();
+
+        let mut logger = Logger::from_config_file(&path).unwrap();
+        assert_eq!(logger.module_threshold("NET"), LogLevel::Verbose);
+        assert_eq!(logger.module_threshold("DB"), LogLevel::Warning);
+        for (module, level) in [("NET", LogLevel::Debug), ("DB", LogLevel::Info)] {
+            logger.set_level(level);
+            logger.append_args(format_args!("hello"));
+            logger.print_with_module(module);
+        }
+        logger.disable_file_logging();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("app.log")).unwrap(),
+            "DEBUG|NET|hello\n"
+        );
+
+        for (document, message) in [
+            (
+                "[console]\ncolour = true",
+                "invalid config key 'console.colour': unknown key",
+            ),
+            (
+                "[file.rotation]\nmax_files = -1",
+                "invalid config key 'file.rotation.max_files': expected a non-negative integer",
+            ),
+            (
+                "[modules]\nNET = \"loud\"",
+                "invalid config key 'modules.NET': unknown log level 'loud'",
+            ),
+            (
+                "[console]\nformat = \"pattern\"",
+                "invalid config key 'console.line_pattern': required by format = \"pattern\"",
+            ),
+        ] {
+            let error = LoggerConfig::parse(document).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+        assert!(matches!(
+            LoggerConfig::parse("include_date = "),
+            Err(LoggerError::InvalidConfig { key, .. }) if key.is_empty()
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_apply_config_failure_keeps_settings() {
+        let dir = temp_dir("apply_config");
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "not a directory").unwrap();
+        let config = |directory: &std::path::Path, level: &str| {
+            LoggerConfig::parse(&format!(
+                "[console]\nthreshold = \"fixed\"\n\n[file]\nenabled = true\ndirectory = \"{}\"\npattern = \"app.log\"\n\n[modules]\nNET = \"{}\"\n",
+                directory.display(),
+                level
+            ))
+            .unwrap()
+        };
+
+        let mut logger = Logger::new();
+        logger.apply_config(&config(&dir, "verbose")).unwrap();
+        let path = logger.log_file_path();
+
+        let result = logger.apply_config(&config(&blocker.join("logs"), "error"));
+        assert!(matches!(result, Err(LoggerError::OpenFile { .. })));
+        assert_eq!(logger.module_threshold("NET"), LogLevel::Verbose);
+        assert_eq!(
+            logger.file_config().directory.as_deref(),
+            Some(dir.as_path())
+        );
+        assert_eq!(logger.log_file_path(), path);
+
+        logger.disable_file_logging();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    //
+    // -----------------------------
+    //  Builder
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
+    fn test_logger_builder() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let mut logger = LoggerBuilder::new()
+            .console_threshold(LogLevel::Fixed)
+            .default_module_threshold(LogLevel::Info)
+            .module_threshold("NET", LogLevel::Debug)
+            .sink(Box::new(CaptureSink {
+                lines: lines.clone(),
+                threshold: LogLevel::Verbose,
+            }))
+            .build()
+            .unwrap();
+        for (module, level) in [("NET", LogLevel::Debug), ("DB", LogLevel::Debug)] {
+            logger.set_level(level);
+            logger.append("x");
+            logger.print_with_module(module);
+        }
+        assert_eq!(*lines.lock().unwrap(), ["  DEBUG|NET|x "]);
+
+        let error = LoggerBuilder::new()
+            .file_threshold(LogLevel::Error)
+            .file_max_level(LogLevel::Info)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "invalid config key 'file.max_level': below file.threshold"
+        );
+
+        // A failed install keeps the current global logger
+        let _guard = reset_logger();
+        let global = capture_global();
+        assert!(LoggerBuilder::new()
+            .async_writer(AsyncConfig {
+                capacity: 0,
+                ..AsyncConfig::default()
+            })
+            .install()
+            .is_err());
+        log_info!("still here");
+        assert_eq!(global.lock().unwrap().len(), 1);
+
+        LoggerBuilder::new()
+            .console_threshold(LogLevel::Warning)
+            .use_colors(false)
+            .install()
+            .unwrap();
+        assert_eq!(max_enabled_level(), LogLevel::Warning);
+        log_info!("dropped");
+        assert_eq!(global.lock().unwrap().len(), 1);
+    }
+
+    //
+    // -----------------------------
+    //  Config hot reload
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "warning", ignore = "stripped by the level cap")]
+    fn test_config_hot_reload() {
+        let _guard = reset_logger();
+        let lines = capture_global();
+        let dir = temp_dir("reload");
+        let path = dir.join("ulogger.toml");
+        let config = |module_level: &str| {
+            format!(
+                "include_date = false\ndefault_level = \"error\"\n\n[console]\nthreshold = \"fixed\"\ncolors = false\n\n[modules]\nNET = \"{}\"\n",
+                module_level
+            )
+        };
+        std::fs::write(&path, config("warning")).unwrap();
+
+        let watcher = ConfigWatcher::start(&path, std::time::Duration::from_millis(10)).unwrap();
+        assert_eq!(max_enabled_level(), LogLevel::Warning);
+
+        std::fs::write(&path, config("debug")).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while LOGGER.lock().unwrap().module_threshold("NET") != LogLevel::Debug
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        drop(watcher);
+
+        assert_eq!(
+            LOGGER.lock().unwrap().module_threshold("NET"),
+            LogLevel::Debug
+        );
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("  FIXED||config reloaded from "));
+        assert!(lines[0].ends_with(": modules.NET: Some(Warning) -> Some(Debug)"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    //
+    // -----------------------------
+    //  Independent logger instances
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
+    fn test_logger_handle_macros() {
+        let _guard = reset_logger();
+        let global = capture_global();
+
+        let (logger, lines) = capture_logger(LogLevel::Info);
+        let handle = LoggerHandle::from(logger);
+        let clone = handle.clone();
+        assert_eq!(handle.max_enabled_level(), LogLevel::Info);
+
+        log_module!("DB");
+        log_info!(target: &handle, "connected to {}", "primary");
+        log_debug!(target: &clone, "skipped");
+        log_print!(target: &clone, LogLevel::Error, log_i32!(7), log_kv!("retry", true));
+        clone.lock().set_default_module_threshold(LogLevel::Fatal);
+        assert_eq!(handle.max_enabled_level(), LogLevel::Fatal);
+        log_error!(target: &handle, "filtered");
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            ["   INFO|DB|connected to primary", "  ERROR|DB|7 retry=true"]
+        );
+        assert!(global.lock().unwrap().is_empty());
+
+        log_info!(target: &LoggerHandle::global(), "global");
+        assert_eq!(*global.lock().unwrap(), ["   INFO|DB|global"]);
+    }
+
+    //
+    // -----------------------------
+    //  Named logger hierarchy
+    // -----------------------------
+    //
+    #[test]
+    #[cfg_attr(ulogger_strips = "debug", ignore = "stripped by the level cap")]
+    fn test_named_logger_registry() {
+        let _guard = reset_logger();
+        let global = capture_global();
+
+        let (app, app_lines) = capture_logger(LogLevel::Verbose);
+        let app = LoggerHandle::from(app);
+        app.lock().set_module_threshold("app.db", LogLevel::Warning);
+        app.lock()
+            .set_module_threshold("app.net.tls.cache", LogLevel::Error);
+        let (tls, tls_lines) = capture_logger(LogLevel::Verbose);
+        register_logger("app", app.clone());
+        register_logger("app.net.tls", tls);
+        // No outputs of its own, everything goes up to "app"
+        let mut quiet = Logger::new();
+        quiet.set_console_threshold(LogLevel::Fixed);
+        register_logger("app.quiet", quiet);
+
+        log_module!("app.net");
+        log_info!("handshake");
+        log_module!("app.net.tls.session");
+        log_debug!("resumed");
+        log_module!("app.net.tls.cache");
+        log_warning!("filtered by app");
+        log_module!("app.quiet");
+        log_info!("propagated");
+        log_module!("app.db.pool");
+        log_info!("filtered by app.db");
+        log_warning!("pool exhausted");
+        log_module!("application");
+        log_info!("not a child of app");
+
+        assert_eq!(
+            named_logger("app.db.pool")
+                .lock()
+                .module_threshold("app.db.pool"),
+            LogLevel::Warning
+        );
+
+        unregister_logger("app.net.tls");
+        unregister_logger("app.quiet");
+        log_module!("app.net.tls");
+        log_print!(LogLevel::Error, log_str!("back to app"));
+        unregister_logger("app");
+        log_info!("global again");
+
+        assert_eq!(
+            *app_lines.lock().unwrap(),
+            [
+                "   INFO|app.net|handshake",
+                "  DEBUG|app.net.tls.session|resumed",
+                "   INFO|app.quiet|propagated",
+                "WARNING|app.db.pool|pool exhausted",
+                "  ERROR|app.net.tls|back to app "
+            ]
+        );
+        assert_eq!(
+            *tls_lines.lock().unwrap(),
+            ["  DEBUG|app.net.tls.session|resumed"]
+        );
+        assert_eq!(
+            *global.lock().unwrap(),
+            [
+                "   INFO|application|not a child of app",
+                "   INFO|app.net.tls|global again"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
+    fn test_named_logger_propagation_thresholds() {
+        let _guard = reset_logger();
+        let (mut app, app_lines) = capture_logger(LogLevel::Verbose);
+        app.set_default_module_threshold(LogLevel::Warning);
+        register_logger("app", app);
+        let mut net = Logger::new();
+        net.set_console_threshold(LogLevel::Fixed);
+        register_logger("app.net", net);
+
+        // The parent's default gates its own records, not its children's
+        log_module!("app");
+        log_info!("parent info");
+        log_module!("app.net");
+        log_info!("child info");
+
+        let (mut net, net_lines) = capture_logger(LogLevel::Verbose);
+        net.set_console_threshold(LogLevel::Fixed);
+        register_logger("app.net", net);
+        log_info!("child info again");
+        unregister_logger("app.net");
+        unregister_logger("app");
+
+        assert_eq!(
+            *app_lines.lock().unwrap(),
+            [
+                "   INFO|app.net|child info",
+                "   INFO|app.net|child info again"
+            ]
+        );
+        assert_eq!(
+            *net_lines.lock().unwrap(),
+            ["   INFO|app.net|child info again"]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(ulogger_strips = "info", ignore = "stripped by the level cap")]
+    fn test_named_logger_reregistered() {
+        let _guard = reset_logger();
+        let (first, first_lines) = capture_logger(LogLevel::Verbose);
+        let (second, second_lines) = capture_logger(LogLevel::Verbose);
+
+        log_module!("app");
+        register_logger("app", first);
+        log_info!("one");
+        unregister_logger("app");
+        // Same name on the same thread: the cached lookup must not survive
+        register_logger("app", second);
+        log_info!("two");
+        unregister_logger("app");
+
+        assert_eq!(*first_lines.lock().unwrap(), ["   INFO|app|one"]);
+        assert_eq!(*second_lines.lock().unwrap(), ["   INFO|app|two"]);
+    }
 }
@@ -0,0 +1,84 @@
+use crate::registry::{has_named_loggers, target_logger};
+use crate::{max_enabled_level, LogLevel, Logger, LOGGER, LOG_MODULE};
+
+// ---------- log crate bridge ----------
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => LogLevel::Verbose,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warning,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
+/// `log::Log` adapter that forwards every record to the global `LOGGER`, or
+/// to the named logger registered for its module tag or target.
+pub struct LogBridge;
+
+impl log::Log for LogBridge {
+    /// Lock-free: answers from the level hint only, and lets everything
+    /// through while named loggers are registered.
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        LogLevel::from(metadata.level()) >= max_enabled_level() || has_named_loggers()
+    }
+
+    fn log(&self, record: &log::Record) {
+        let level = record.level().into();
+
+        // The ulogger module tag wins; fall back to the `log` target
+        let module = LOG_MODULE.with(|m| *m.borrow());
+        let module = if module.is_empty() {
+            record.target()
+        } else {
+            module
+        };
+
+        if let Some(handle) = target_logger(module) {
+            if level >= handle.max_enabled_level() {
+                let mut logger = handle.lock();
+                fill(&mut logger, level, record);
+                handle.print_with_module(&mut logger, module);
+            }
+            return;
+        }
+
+        if level < max_enabled_level() {
+            return;
+        }
+        let mut logger = lock_logger();
+        if !logger.enabled(level) {
+            return;
+        }
+        fill(&mut logger, level, record);
+        logger.print_with_module(module);
+    }
+
+    fn flush(&self) {
+        lock_logger().flush();
+    }
+}
+
+fn fill(logger: &mut Logger, level: LogLevel, record: &log::Record) {
+    logger.set_level(level);
+    if let (Some(file), Some(line)) = (record.file_static(), record.line()) {
+        logger.set_location(file, line);
+    }
+    logger.append_args(*record.args());
+}
+
+/// A panic on another thread must not take the `log` macros down with it.
+fn lock_logger() -> std::sync::MutexGuard<'static, Logger> {
+    LOGGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+static BRIDGE: LogBridge = LogBridge;
+
+/// Registers the global `LOGGER` as the `log` crate backend.
+pub fn init_log_bridge() -> Result<(), log::SetLoggerError> {
+    log::set_logger(&BRIDGE)?;
+    log::set_max_level(log::LevelFilter::Trace);
+    Ok(())
+}
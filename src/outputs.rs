@@ -0,0 +1,191 @@
+use crate::{
+    ConsoleSink, ErrorHandler, FieldValue, FileFallback, FileSink, LogLevel, LoggerError, Record,
+    Sink,
+};
+use chrono::{DateTime, Local};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+// ---------- Outputs ----------
+/// Every destination of a logger plus its error bookkeeping. Shared with the
+/// async writer thread, so it lives behind its own mutex.
+pub(crate) struct Outputs {
+    pub(crate) console: ConsoleSink,
+    pub(crate) file: Option<FileSink>,
+    pub(crate) file_fallback: FileFallback,
+    pub(crate) sinks: Vec<Box<dyn Sink>>,
+    pub(crate) error_count: u64,
+    pub(crate) error_handler: Option<ErrorHandler>,
+}
+
+pub(crate) type OutputsRef = Arc<Mutex<Outputs>>;
+
+impl Outputs {
+    pub(crate) fn new() -> Self {
+        Self {
+            console: ConsoleSink::new(),
+            file: None,
+            file_fallback: FileFallback::Discard,
+            sinks: Vec::new(),
+            error_count: 0,
+            error_handler: None,
+        }
+    }
+
+    pub(crate) fn enabled(&self, level: LogLevel) -> bool {
+        self.console.enabled(level)
+            || self.file.as_ref().is_some_and(|f| f.enabled(level))
+            || self.sinks.iter().any(|s| s.enabled(level))
+    }
+
+    /// Lowest threshold across all outputs; upper bounds are ignored.
+    pub(crate) fn min_threshold(&self) -> LogLevel {
+        let file = self.file.as_ref().map(|f| f.threshold);
+        let sinks = self.sinks.iter().map(|s| s.threshold());
+        std::iter::once(self.console.threshold)
+            .chain(file)
+            .chain(sinks)
+            .fold(
+                LogLevel::Fixed,
+                |min, level| if level < min { level } else { min },
+            )
+    }
+
+    pub(crate) fn dispatch(&mut self, record: &Record) {
+        let mut failures = Vec::new();
+
+        if self.console.enabled(record.level) {
+            let _ = self.console.write(record);
+        }
+
+        if let Some(file) = &mut self.file {
+            if file.enabled(record.level) {
+                if let Err(source) = file.write(record) {
+                    if self.file_fallback == FileFallback::Stderr {
+                        eprint!("{}", file.format(record));
+                    }
+                    failures.push(LoggerError::WriteFile {
+                        path: file.path().into(),
+                        source,
+                    });
+                }
+            }
+            if let Err(source) = file.compressor_result(false) {
+                failures.push(LoggerError::WriteFile {
+                    path: file.path().into(),
+                    source,
+                });
+            }
+        }
+
+        for sink in &mut self.sinks {
+            if sink.enabled(record.level) {
+                if let Err(source) = sink.write(record) {
+                    failures.push(LoggerError::Sink(source));
+                }
+            }
+        }
+
+        for error in failures {
+            self.report(error);
+        }
+    }
+
+    pub(crate) fn flush(&mut self) {
+        let _ = self.console.flush();
+        if let Some(mut file) = self.file.take() {
+            self.finish_file(&mut file);
+            self.file = Some(file);
+        }
+        for sink in &mut self.sinks {
+            let _ = sink.flush();
+        }
+    }
+
+    /// Flushes and closes the log file, waiting for any background compression.
+    pub(crate) fn close_file(&mut self) {
+        if let Some(mut file) = self.file.take() {
+            self.finish_file(&mut file);
+        }
+    }
+
+    fn finish_file(&mut self, file: &mut FileSink) {
+        let results = [file.flush(), file.compressor_result(true)];
+        for source in results.into_iter().filter_map(Result::err) {
+            let path = file.path().into();
+            self.report(LoggerError::WriteFile { path, source });
+        }
+    }
+
+    pub(crate) fn report(&mut self, error: LoggerError) {
+        self.error_count += 1;
+        if let Some(handler) = &self.error_handler {
+            handler(&error);
+        }
+    }
+}
+
+// ---------- Interval flusher ----------
+/// Flushes the log file once buffered records are older than the flush
+/// policy's interval, even when no further records arrive. Stops when dropped.
+pub(crate) struct IntervalFlusher {
+    pub(crate) interval: Duration,
+    _stop: mpsc::Sender<()>,
+}
+
+impl IntervalFlusher {
+    pub(crate) fn start(interval: Duration, outputs: OutputsRef) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name(String::from("ulogger-flusher"))
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let mut outputs = outputs.lock().unwrap_or_else(|e| e.into_inner());
+                    let Some(file) = &mut outputs.file else {
+                        continue;
+                    };
+                    if file.flush_overdue() {
+                        if let Err(source) = file.flush() {
+                            let path = file.path().into();
+                            outputs.report(LoggerError::WriteFile { path, source });
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn the ulogger flusher thread");
+
+        Self {
+            interval,
+            _stop: stop,
+        }
+    }
+}
+
+// ---------- Owned record ----------
+/// A `Record` detached from the logger's buffers, for the async writer.
+#[derive(Clone)]
+pub(crate) struct OwnedRecord {
+    pub(crate) level: LogLevel,
+    pub(crate) time: DateTime<Local>,
+    pub(crate) timestamp: String,
+    pub(crate) module: String,
+    pub(crate) thread: String,
+    pub(crate) location: Option<(&'static str, u32)>,
+    pub(crate) message: String,
+    pub(crate) fields: Vec<(String, FieldValue)>,
+}
+
+impl OwnedRecord {
+    pub(crate) fn as_record(&self) -> Record<'_> {
+        Record {
+            level: self.level,
+            time: self.time,
+            timestamp: &self.timestamp,
+            module: &self.module,
+            thread: &self.thread,
+            location: self.location,
+            message: &self.message,
+            fields: &self.fields,
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{LoggerError, Record};
+use std::fmt::Write as _;
+
+// ---------- Line pattern ----------
+/// A user-defined line layout such as
+/// `"%d{%H:%M:%S%.3f} [%l] %m{:<12} %t: %msg"`, compiled once.
+///
+/// Tokens: `%d{strftime}` timestamp, `%l` level, `%i` level icon, `%m` module,
+/// `%t` thread, `%p` process id, `%s` source `file:line`, `%msg` message with
+/// fields, `%%` a literal `%`. Any token may be followed by `{:<W.P}` (align
+/// `<`, `>` or `^`, minimum width `W`, truncation to `P` characters).
+#[derive(Debug, Clone)]
+pub struct LinePattern {
+    source: String,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Literal(String),
+    Token(Token, Spec),
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Date(String),
+    Level,
+    Icon,
+    Module,
+    Thread,
+    Pid,
+    Source,
+    Message,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Spec {
+    align: Align,
+    width: Option<usize>,
+    max: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+impl PartialEq for LinePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for LinePattern {}
+
+impl LinePattern {
+    pub fn compile(pattern: &str) -> Result<Self, LoggerError> {
+        let error = |reason: &str| LoggerError::InvalidLinePattern {
+            pattern: pattern.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut rest = pattern;
+
+        while let Some(pos) = rest.find('%') {
+            literal.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            if let Some(after) = rest.strip_prefix('%') {
+                literal.push('%');
+                rest = after;
+                continue;
+            }
+
+            let (token, after) = if let Some(after) = rest.strip_prefix("msg") {
+                (Token::Message, after)
+            } else if let Some(after) = rest.strip_prefix('d') {
+                match braced(after) {
+                    Some((inner, after)) if !inner.starts_with(':') => {
+                        (Token::Date(inner.to_string()), after)
+                    }
+                    _ => (Token::Date(String::from("%Y-%m-%d %H:%M:%S%.6f")), after),
+                }
+            } else {
+                let mut chars = rest.chars();
+                let token = match chars.next() {
+                    Some('l') => Token::Level,
+                    Some('i') => Token::Icon,
+                    Some('m') => Token::Module,
+                    Some('t') => Token::Thread,
+                    Some('p') => Token::Pid,
+                    Some('s') => Token::Source,
+                    Some(_) => return Err(error("unknown token")),
+                    None => return Err(error("dangling '%' at the end")),
+                };
+                (token, chars.as_str())
+            };
+
+            if let Token::Date(format) = &token {
+                // Validate the strftime specifiers once, at compile time
+                if write!(String::new(), "{}", chrono::Local::now().format(format)).is_err() {
+                    return Err(error("invalid strftime specifier in %d{...}"));
+                }
+            }
+
+            let (spec, after) = match braced(after) {
+                Some((inner, after)) if inner.starts_with(':') => (
+                    parse_spec(&inner[1..]).ok_or_else(|| error("invalid {:...} spec"))?,
+                    after,
+                ),
+                _ => (Spec::default(), after),
+            };
+
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+            }
+            pieces.push(Piece::Token(token, spec));
+            rest = after;
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+
+        Ok(Self {
+            source: pattern.to_string(),
+            pieces,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Renders one line, including the trailing newline.
+    pub fn render(&self, record: &Record) -> String {
+        let mut out = String::with_capacity(64 + record.message.len());
+        let mut value = String::new();
+
+        for piece in &self.pieces {
+            let (token, spec) = match piece {
+                Piece::Literal(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Piece::Token(token, spec) => (token, spec),
+            };
+
+            value.clear();
+            let _ = match token {
+                Token::Date(format) => write!(value, "{}", record.time.format(format)),
+                Token::Level => write!(value, "{}", record.level.as_str()),
+                Token::Icon => write!(value, "{}", record.level.icon()),
+                Token::Module => write!(value, "{}", record.module),
+                Token::Thread => write!(value, "{}", record.thread),
+                Token::Pid => write!(value, "{}", std::process::id()),
+                Token::Source => match record.location {
+                    Some((file, line)) => write!(value, "{}:{}", file, line),
+                    None => Ok(()),
+                },
+                Token::Message => write!(value, "{}", record.text().trim_end()),
+            };
+            spec.apply(&value, &mut out);
+        }
+
+        out.push('\n');
+        out
+    }
+}
+
+impl Spec {
+    fn apply(&self, value: &str, out: &mut String) {
+        let value = match self.max {
+            Some(max) => match value.char_indices().nth(max) {
+                Some((end, _)) => &value[..end],
+                None => value,
+            },
+            None => value,
+        };
+
+        let pad = self
+            .width
+            .unwrap_or(0)
+            .saturating_sub(value.chars().count());
+        let (left, right) = match self.align {
+            Align::Left => (0, pad),
+            Align::Right => (pad, 0),
+            Align::Center => (pad / 2, pad - pad / 2),
+        };
+
+        out.extend(std::iter::repeat_n(' ', left));
+        out.push_str(value);
+        out.extend(std::iter::repeat_n(' ', right));
+    }
+}
+
+/// Splits `{inner}rest` into (`inner`, `rest`).
+fn braced(s: &str) -> Option<(&str, &str)> {
+    let s = s.strip_prefix('{')?;
+    let end = s.find('}')?;
+    Some((&s[..end], &s[end + 1..]))
+}
+
+/// Parses `<12`, `>8.8`, `^10`, `.5`, `12` (the part after `{:`).
+fn parse_spec(s: &str) -> Option<Spec> {
+    let mut spec = Spec::default();
+    let s = match s.chars().next() {
+        Some('<') => &s[1..],
+        Some('>') => {
+            spec.align = Align::Right;
+            &s[1..]
+        }
+        Some('^') => {
+            spec.align = Align::Center;
+            &s[1..]
+        }
+        _ => s,
+    };
+
+    let (width, max) = match s.split_once('.') {
+        Some((width, max)) => (width, Some(max)),
+        None => (s, None),
+    };
+    if !width.is_empty() {
+        spec.width = Some(width.parse().ok()?);
+    }
+    if let Some(max) = max {
+        spec.max = Some(max.parse().ok()?);
+    }
+    Some(spec)
+}
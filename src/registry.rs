@@ -0,0 +1,113 @@
+use crate::LoggerHandle;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// ---------- Named logger registry ----------
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<HashMap<String, LoggerHandle>> = Mutex::new(HashMap::new());
+}
+
+/// Bumped on every change so per-thread lookups can be cached; never reused.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Lets the macros skip the lookup while nothing is registered.
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static LOOKUP_CACHE: RefCell<Option<(&'static str, u64, Option<LoggerHandle>)>> =
+        const { RefCell::new(None) };
+}
+
+fn registry() -> std::sync::MutexGuard<'static, HashMap<String, LoggerHandle>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn changed(registry: &HashMap<String, LoggerHandle>) {
+    GENERATION.fetch_add(1, Ordering::Release);
+    REGISTERED.store(!registry.is_empty(), Ordering::Release);
+}
+
+/// Registers the logger for `name` and every dotted descendant
+/// (`"app"` covers `"app.net"` and `"app.net.tls"`) without one of its own.
+/// Records of registered descendants also propagate to this logger.
+pub fn register_logger(name: impl Into<String>, logger: impl Into<LoggerHandle>) {
+    let mut registry = registry();
+    registry.insert(name.into(), logger.into().with_ancestors(Vec::new()));
+    changed(&registry);
+}
+
+pub fn unregister_logger(name: &str) -> Option<LoggerHandle> {
+    let mut registry = registry();
+    let removed = registry.remove(name);
+    changed(&registry);
+    removed
+}
+
+/// The logger registered for `name` or its nearest ancestor, falling back to
+/// the global `LOGGER`. Records logged through the returned handle also reach
+/// the outputs of the loggers registered further up the name.
+pub fn named_logger(name: &str) -> LoggerHandle {
+    resolve(&registry(), name).unwrap_or_else(LoggerHandle::global)
+}
+
+/// Whether any named logger is registered. Read without locking.
+#[cfg(feature = "log")]
+pub(crate) fn has_named_loggers() -> bool {
+    REGISTERED.load(Ordering::Acquire)
+}
+
+/// `named_logger` for a `log`/`tracing` target (`::` is read as `.`), or
+/// `None` for the global `LOGGER`; cheap while the registry is empty.
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub(crate) fn target_logger(target: &str) -> Option<LoggerHandle> {
+    if !REGISTERED.load(Ordering::Acquire) {
+        return None;
+    }
+    resolve(&registry(), &target.replace("::", "."))
+}
+
+fn resolve(registry: &HashMap<String, LoggerHandle>, mut name: &str) -> Option<LoggerHandle> {
+    let mut chain: Vec<LoggerHandle> = Vec::new();
+    loop {
+        if let Some(handle) = registry.get(name) {
+            // A logger registered under several names only writes once
+            if !chain.iter().any(|seen| seen.same_logger(handle)) {
+                chain.push(handle.clone());
+            }
+        }
+        match name.rfind('.') {
+            Some(pos) => name = &name[..pos],
+            None => break,
+        }
+    }
+
+    let mut chain = chain.into_iter();
+    let nearest = chain.next()?;
+    Some(nearest.with_ancestors(chain.collect()))
+}
+
+/// The named logger selected by the thread's `LOG_MODULE` tag, or `None`
+/// for the global `LOGGER`. Used by the logging macros.
+#[doc(hidden)]
+pub fn module_logger() -> Option<LoggerHandle> {
+    if !REGISTERED.load(Ordering::Acquire) {
+        return None;
+    }
+    let generation = GENERATION.load(Ordering::Acquire);
+
+    let module = crate::LOG_MODULE.with(|m| *m.borrow());
+    LOOKUP_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        match &*cache {
+            Some((cached, seen, handle)) if *cached == module && *seen == generation => {
+                handle.clone()
+            }
+            _ => {
+                let handle = resolve(&registry(), module);
+                *cache = Some((module, generation, handle.clone()));
+                handle
+            }
+        }
+    })
+}
@@ -0,0 +1,99 @@
+use crate::{LogLevel, LoggerConfig, LoggerError};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+// ---------- Config hot reload ----------
+/// Keeps the global `LOGGER` in sync with a TOML configuration file.
+///
+/// The file is applied once by `start`, then polled every `interval`; when
+/// its modification time or size changes it is re-read and the new settings
+/// are applied under the logger lock, followed by a `Fixed` record listing the
+/// changes. Invalid files, and files whose log file cannot be opened, are
+/// reported through the error handler and the previous settings stay in
+/// effect. Dropping the watcher stops the thread.
+pub struct ConfigWatcher {
+    stop: Option<mpsc::Sender<()>>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    pub fn start(path: impl AsRef<Path>, interval: Duration) -> Result<Self, LoggerError> {
+        let path = path.as_ref().to_path_buf();
+        let mut stamp = file_stamp(&path);
+        let mut current = LoggerConfig::load(&path)?;
+        lock_logger().apply_config(&current)?;
+
+        let (stop, stopped) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name(String::from("ulogger-config"))
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let latest = file_stamp(&path);
+                    if latest.is_none() || latest == stamp {
+                        continue;
+                    }
+                    stamp = latest;
+                    if let Some(config) = reload(&path, &current) {
+                        current = config;
+                    }
+                }
+            })
+            .expect("failed to spawn the ulogger config thread");
+
+        Ok(Self {
+            stop: Some(stop),
+            worker: Some(worker),
+        })
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn lock_logger() -> std::sync::MutexGuard<'static, crate::Logger> {
+    crate::LOGGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Applies the file if it differs from `current`; returns the new settings,
+/// or `None` when they could not be loaded or applied.
+fn reload(path: &Path, current: &LoggerConfig) -> Option<LoggerConfig> {
+    let config = LoggerConfig::load(path);
+    let mut logger = lock_logger();
+    let config = match config {
+        Ok(config) => config,
+        Err(error) => {
+            logger.outputs().report(error);
+            return None;
+        }
+    };
+
+    let changes = current.diff(&config);
+    if changes.is_empty() {
+        return Some(config);
+    }
+    if let Err(error) = logger.apply_config(&config) {
+        logger.outputs().report(error);
+        return None;
+    }
+
+    logger.set_level(LogLevel::Fixed);
+    logger.append_args(format_args!(
+        "config reloaded from {}: {}",
+        path.display(),
+        changes.join(", ")
+    ));
+    logger.print();
+    Some(config)
+}
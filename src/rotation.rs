@@ -0,0 +1,279 @@
+use chrono::{DateTime, Local};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::path::{Path, PathBuf};
+
+// ---------- Rotation policy ----------
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RotationInterval {
+    Hourly,
+    Daily,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RotationNaming {
+    /// `log.txt` -> `log.1.txt`, older segments shift to `log.2.txt`, ...
+    Numbered,
+    /// `log.txt` -> `log.2025-01-31.txt` (stamped with the segment's start time)
+    Dated,
+}
+
+/// When and how the active log file is rolled over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationPolicy {
+    pub max_bytes: Option<u64>,
+    pub interval: Option<RotationInterval>,
+    pub naming: RotationNaming,
+    /// Number of rotated segments to keep; `None` keeps all of them.
+    pub max_files: Option<usize>,
+    /// Upper bound for the combined size of all rotated segments.
+    pub max_total_bytes: Option<u64>,
+    /// Gzip rotated segments in a background thread (`log.1.txt.gz`).
+    pub compress: bool,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: None,
+            interval: None,
+            naming: RotationNaming::Numbered,
+            max_files: None,
+            max_total_bytes: None,
+            compress: false,
+        }
+    }
+}
+
+impl RotationPolicy {
+    pub(crate) fn should_rotate(
+        &self,
+        written: u64,
+        incoming: u64,
+        opened_at: &DateTime<Local>,
+        now: &DateTime<Local>,
+    ) -> bool {
+        let size_exceeded = self
+            .max_bytes
+            .is_some_and(|max| written > 0 && written + incoming > max);
+
+        let period_changed = self
+            .interval
+            .is_some_and(|interval| period_key(interval, opened_at) != period_key(interval, now));
+
+        size_exceeded || period_changed
+    }
+
+    fn stamp(&self, opened_at: &DateTime<Local>) -> String {
+        match self.interval {
+            Some(RotationInterval::Daily) => opened_at.format("%Y-%m-%d").to_string(),
+            Some(RotationInterval::Hourly) => opened_at.format("%Y-%m-%d_%H").to_string(),
+            None => opened_at.format("%Y-%m-%d_%H%M%S").to_string(),
+        }
+    }
+}
+
+fn period_key(interval: RotationInterval, time: &DateTime<Local>) -> String {
+    match interval {
+        RotationInterval::Hourly => time.format("%Y%m%d%H").to_string(),
+        RotationInterval::Daily => time.format("%Y%m%d").to_string(),
+    }
+}
+
+// ---------- Sibling naming ----------
+/// Splits `dir/name.ext` into (`dir`, `name`, `.ext`).
+fn split_path(path: &Path) -> (PathBuf, String, String) {
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (dir, stem, ext)
+}
+
+fn sibling(path: &Path, tag: &str) -> PathBuf {
+    let (dir, stem, ext) = split_path(path);
+    dir.join(format!("{}.{}{}", stem, tag, ext))
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// The segment stored under `tag`, either plain or already compressed.
+fn existing_sibling(path: &Path, tag: &str) -> Option<PathBuf> {
+    let plain = sibling(path, tag);
+    let compressed = gz_path(&plain);
+    if plain.exists() {
+        Some(plain)
+    } else if compressed.exists() {
+        Some(compressed)
+    } else {
+        None
+    }
+}
+
+/// Whether `tag` is one `rotate` produces: a segment number, or a date
+/// stamp with an optional collision counter (`2025-01-31_14.2`).
+fn is_segment_tag(tag: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if digits(tag) {
+        return true;
+    }
+
+    let (stamp, counter) = match tag.split_once('.') {
+        Some((stamp, counter)) => (stamp, Some(counter)),
+        None => (tag, None),
+    };
+    let (date, time) = match stamp.split_once('_') {
+        Some((date, time)) => (date, Some(time)),
+        None => (stamp, None),
+    };
+    let date_ok = date.len() == 10
+        && date.split('-').map(str::len).eq([4, 2, 2])
+        && date.split('-').all(digits);
+    let time_ok = time.is_none_or(|t| (t.len() == 2 || t.len() == 6) && digits(t));
+    date_ok && time_ok && counter.is_none_or(digits)
+}
+
+/// Rotated segments (plain or gzipped) that belong to `path`, oldest first.
+/// Other files sharing the stem (`app.net.log` next to `app.log`) are left out.
+pub(crate) fn rotated_siblings(path: &Path) -> Vec<PathBuf> {
+    let (dir, stem, ext) = split_path(path);
+    let prefix = format!("{}.", stem);
+    let lookup = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir.as_path()
+    };
+
+    let mut found: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(lookup)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let rest = name.strip_prefix(&prefix).unwrap_or_default();
+                    let rest = rest.strip_suffix(".gz").unwrap_or(rest);
+                    rest.strip_suffix(ext.as_str()).is_some_and(is_segment_tag)
+                })
+                .filter_map(|entry| {
+                    let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+                    Some((modified, dir.join(entry.file_name())))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    found.sort();
+    found.into_iter().map(|(_, p)| p).collect()
+}
+
+// ---------- Rotation ----------
+/// Moves the active file at `path` out of the way.
+/// Returns the path the active file was renamed to, if it was kept.
+pub(crate) fn rotate(
+    path: &Path,
+    policy: &RotationPolicy,
+    opened_at: &DateTime<Local>,
+) -> std::io::Result<Option<PathBuf>> {
+    if policy.max_files == Some(0) {
+        std::fs::remove_file(path)?;
+        return Ok(None);
+    }
+
+    let target = match policy.naming {
+        RotationNaming::Numbered => {
+            let mut highest = 0;
+            while existing_sibling(path, &(highest + 1).to_string()).is_some() {
+                highest += 1;
+            }
+            for index in (1..=highest).rev() {
+                let Some(from) = existing_sibling(path, &index.to_string()) else {
+                    continue;
+                };
+                if policy.max_files.is_some_and(|max| index >= max) {
+                    std::fs::remove_file(&from)?;
+                } else {
+                    let mut to = sibling(path, &(index + 1).to_string());
+                    if from.extension().is_some_and(|e| e == "gz") {
+                        to = gz_path(&to);
+                    }
+                    std::fs::rename(&from, to)?;
+                }
+            }
+            sibling(path, "1")
+        }
+        RotationNaming::Dated => {
+            let stamp = policy.stamp(opened_at);
+            let mut tag = stamp.clone();
+            let mut counter = 1;
+            while existing_sibling(path, &tag).is_some() {
+                tag = format!("{}.{}", stamp, counter);
+                counter += 1;
+            }
+            sibling(path, &tag)
+        }
+    };
+
+    std::fs::rename(path, &target)?;
+    Ok(Some(target))
+}
+
+/// Compresses the freshly rotated `segment` (if requested) and enforces the
+/// retention limits. Runs on a background thread when compression is enabled.
+pub(crate) fn finish_rotation(
+    path: &Path,
+    segment: Option<PathBuf>,
+    policy: &RotationPolicy,
+) -> std::io::Result<()> {
+    if policy.compress {
+        if let Some(segment) = segment {
+            compress(&segment)?;
+        }
+    }
+
+    let mut siblings = rotated_siblings(path);
+
+    if policy.naming == RotationNaming::Dated {
+        if let Some(max) = policy.max_files {
+            let excess = siblings.len().saturating_sub(max);
+            for old in siblings.drain(..excess) {
+                std::fs::remove_file(old)?;
+            }
+        }
+    }
+
+    if let Some(max_total) = policy.max_total_bytes {
+        let mut total: u64 = siblings
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum();
+        for old in &siblings {
+            if total <= max_total {
+                break;
+            }
+            total -= std::fs::metadata(old).map(|m| m.len()).unwrap_or(0);
+            std::fs::remove_file(old)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn compress(path: &Path) -> std::io::Result<PathBuf> {
+    let target = gz_path(path);
+    let mut input = std::fs::File::open(path)?;
+    let mut encoder = GzEncoder::new(std::fs::File::create(&target)?, Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(path)?;
+    Ok(target)
+}
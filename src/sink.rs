@@ -0,0 +1,421 @@
+use crate::field::{self, FieldValue};
+use crate::format::LogFormat;
+use crate::rotation::{self, RotationPolicy};
+use crate::{LogLevel, LoggerError};
+use chrono::{DateTime, Local};
+use std::io::Write;
+
+// ---------- Record ----------
+/// A single log line as handed to every sink.
+pub struct Record<'a> {
+    pub level: LogLevel,
+    pub time: DateTime<Local>,
+    /// `time` rendered for the text layout, including the trailing separator.
+    pub timestamp: &'a str,
+    pub module: &'a str,
+    pub thread: &'a str,
+    /// Source `(file, line)` of the logging call, when known.
+    pub location: Option<(&'a str, u32)>,
+    pub message: &'a str,
+    pub fields: &'a [(String, FieldValue)],
+}
+
+impl Record<'_> {
+    /// The message followed by its fields as `key=value` pairs.
+    pub fn text(&self) -> String {
+        let mut text = self.message.to_string();
+        field::write_fields(&mut text, self.fields);
+        text
+    }
+}
+
+// ---------- Sink trait ----------
+/// An output destination for log records.
+pub trait Sink: Send {
+    fn write(&mut self, record: &Record) -> std::io::Result<()>;
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn threshold(&self) -> LogLevel {
+        LogLevel::Verbose
+    }
+
+    /// Optional upper bound; records above it are not written to this sink.
+    fn max_level(&self) -> Option<LogLevel> {
+        None
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        level >= self.threshold() && self.max_level().is_none_or(|max| level <= max)
+    }
+}
+
+// ---------- Console sink ----------
+pub struct ConsoleSink {
+    pub threshold: LogLevel,
+    pub max_level: Option<LogLevel>,
+    pub use_colors: bool,
+    pub format: LogFormat,
+}
+
+impl ConsoleSink {
+    pub fn new() -> Self {
+        Self {
+            threshold: LogLevel::Verbose,
+            max_level: None,
+            use_colors: true,
+            format: LogFormat::Text,
+        }
+    }
+}
+
+impl Default for ConsoleSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn write(&mut self, record: &Record) -> std::io::Result<()> {
+        let msg = if let LogFormat::Pattern(pattern) = &self.format {
+            let line = pattern.render(record);
+            if self.use_colors {
+                format!("{}{}\x1b[0m\n", record.level.color(), line.trim_end())
+            } else {
+                line
+            }
+        } else if let Some(line) = self.format.render(record) {
+            line
+        } else if self.use_colors {
+            format!(
+                "{}{}{:>8} | {:<8} | {}\x1b[0m\n",
+                record.level.color(),
+                record.timestamp,
+                record.level,
+                record.module,
+                record.text()
+            )
+        } else {
+            format!(
+                "{}{:>8} | {:<8} | {}\n",
+                record.timestamp,
+                record.level,
+                record.module,
+                record.text()
+            )
+        };
+        print!("{}", msg);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+
+    fn threshold(&self) -> LogLevel {
+        self.threshold
+    }
+
+    fn max_level(&self) -> Option<LogLevel> {
+        self.max_level
+    }
+}
+
+// ---------- Log file location ----------
+/// Where `Logger::enable_file_logging` puts its file and how it opens it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    /// Target directory, created if missing; `None` means the working directory.
+    pub directory: Option<std::path::PathBuf>,
+    /// File name with strftime specifiers and a `{pid}` placeholder.
+    pub pattern: String,
+    /// Keep existing content instead of truncating.
+    pub append: bool,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            pattern: String::from("log_%Y%m%d_%H%M%S.txt"),
+            append: false,
+        }
+    }
+}
+
+impl LogFileConfig {
+    pub fn resolve_path(&self) -> Result<std::path::PathBuf, LoggerError> {
+        use std::fmt::Write as _;
+
+        let pattern = self
+            .pattern
+            .replace("{pid}", &std::process::id().to_string());
+
+        let mut name = String::new();
+        write!(name, "{}", Local::now().format(&pattern))
+            .map_err(|_| LoggerError::InvalidPattern(self.pattern.clone()))?;
+
+        Ok(match &self.directory {
+            Some(dir) => dir.join(name),
+            None => std::path::PathBuf::from(name),
+        })
+    }
+
+    pub fn open(&self) -> Result<FileSink, LoggerError> {
+        let path = self.resolve_path()?;
+        let open_error = |source| LoggerError::OpenFile {
+            path: path.clone(),
+            source,
+        };
+
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(open_error)?;
+        }
+
+        let name = path.to_string_lossy().into_owned();
+        if self.append {
+            FileSink::append(name)
+        } else {
+            FileSink::create(name)
+        }
+        .map_err(open_error)
+    }
+}
+
+// ---------- File flush policy ----------
+/// When buffered file output is pushed to the OS. Conditions combine: the
+/// buffer is flushed as soon as any of them holds. The interval is also
+/// checked by a background timer, so a quiet logger does not keep records
+/// buffered indefinitely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlushPolicy {
+    /// Flush after this many buffered records (`Some(1)` = every record).
+    pub every_records: Option<usize>,
+    /// Flush when the oldest unflushed record is older than this.
+    pub interval: Option<std::time::Duration>,
+    /// Flush immediately for records at or above this level.
+    pub immediate_level: Option<LogLevel>,
+    /// Call `fsync` after every flush.
+    pub sync: bool,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self {
+            every_records: Some(1),
+            interval: None,
+            immediate_level: None,
+            sync: false,
+        }
+    }
+}
+
+// ---------- File sink ----------
+pub struct FileSink {
+    file: std::io::BufWriter<std::fs::File>,
+    path: String,
+    written: u64,
+    opened_at: DateTime<Local>,
+    pending: usize,
+    last_flush: std::time::Instant,
+    pub threshold: LogLevel,
+    pub max_level: Option<LogLevel>,
+    pub use_icons: bool,
+    pub format: LogFormat,
+    pub rotation: Option<RotationPolicy>,
+    pub flush_policy: FlushPolicy,
+    compressor: Option<std::thread::JoinHandle<std::io::Result<()>>>,
+    /// Failure of a finished compressor, kept until it can be reported.
+    compressor_error: Option<std::io::Error>,
+}
+
+impl FileSink {
+    /// Creates (or truncates) the file at `path`.
+    pub fn create(path: impl Into<String>) -> std::io::Result<Self> {
+        Self::with_mode(path.into(), false)
+    }
+
+    /// Opens the file at `path`, keeping any existing content.
+    pub fn append(path: impl Into<String>) -> std::io::Result<Self> {
+        Self::with_mode(path.into(), true)
+    }
+
+    fn with_mode(path: String, append: bool) -> std::io::Result<Self> {
+        let file = Self::open(&path, append)?;
+        let metadata = file.metadata()?;
+
+        // An appended file keeps counting towards its current segment
+        let opened_at = match metadata.modified() {
+            Ok(modified) if metadata.len() > 0 => modified.into(),
+            _ => Local::now(),
+        };
+
+        Ok(Self {
+            file: std::io::BufWriter::new(file),
+            path,
+            written: metadata.len(),
+            opened_at,
+            pending: 0,
+            last_flush: std::time::Instant::now(),
+            threshold: LogLevel::Verbose,
+            max_level: None,
+            use_icons: false,
+            format: LogFormat::Text,
+            rotation: None,
+            flush_policy: FlushPolicy::default(),
+            compressor: None,
+            compressor_error: None,
+        })
+    }
+
+    fn open(path: &str, append: bool) -> std::io::Result<std::fs::File> {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+    }
+
+    pub(crate) fn format(&self, record: &Record) -> String {
+        if let Some(line) = self.format.render(record) {
+            return line;
+        }
+
+        let level_repr = if self.use_icons {
+            record.level.icon()
+        } else {
+            &record.level.to_string()
+        };
+
+        format!(
+            "{}{:>8} | {:<8} | {}\n",
+            record.timestamp,
+            level_repr,
+            record.module,
+            record.text()
+        )
+    }
+
+    fn flush_buffer(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.flush_policy.sync {
+            self.file.get_ref().sync_data()?;
+        }
+        self.pending = 0;
+        self.last_flush = std::time::Instant::now();
+        Ok(())
+    }
+
+    fn flush_due(&self, level: LogLevel) -> bool {
+        let policy = &self.flush_policy;
+        policy.immediate_level.is_some_and(|min| level >= min)
+            || policy.every_records.is_some_and(|n| self.pending >= n)
+            || policy
+                .interval
+                .is_some_and(|interval| self.last_flush.elapsed() >= interval)
+    }
+
+    /// Whether buffered records have waited longer than the flush interval.
+    pub(crate) fn flush_overdue(&self) -> bool {
+        self.pending > 0
+            && self
+                .flush_policy
+                .interval
+                .is_some_and(|interval| self.last_flush.elapsed() >= interval)
+    }
+
+    /// Path of the active file; rotated segments are renamed siblings of it.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Outcome of the background compression and pruning of the last rotated
+    /// segment. Only waits for a running compressor when `wait` is set.
+    pub(crate) fn compressor_result(&mut self, wait: bool) -> std::io::Result<()> {
+        if let Some(error) = self.compressor_error.take() {
+            return Err(error);
+        }
+        match self.compressor.take() {
+            Some(handle) if wait || handle.is_finished() => handle
+                .join()
+                .unwrap_or_else(|_| Err(std::io::Error::other("log compression thread panicked"))),
+            running => {
+                self.compressor = running;
+                Ok(())
+            }
+        }
+    }
+
+    fn rotate_if_needed(&mut self, incoming: u64) -> std::io::Result<()> {
+        let now = Local::now();
+        let policy = match &self.rotation {
+            Some(policy) if policy.should_rotate(self.written, incoming, &self.opened_at, &now) => {
+                policy.clone()
+            }
+            _ => return Ok(()),
+        };
+
+        // Segments must not be renamed while the previous one is still being compressed
+        if let Err(error) = self.compressor_result(true) {
+            self.compressor_error = Some(error);
+        }
+
+        self.flush_buffer()?;
+        let active = std::path::PathBuf::from(&self.path);
+        let segment = rotation::rotate(&active, &policy, &self.opened_at)?;
+        self.file = std::io::BufWriter::new(Self::open(&self.path, false)?);
+        self.written = 0;
+        self.opened_at = now;
+
+        if policy.compress {
+            self.compressor = Some(std::thread::spawn(move || {
+                rotation::finish_rotation(&active, segment, &policy)
+            }));
+            Ok(())
+        } else {
+            rotation::finish_rotation(&active, segment, &policy)
+        }
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        if let Some(handle) = self.compressor.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, record: &Record) -> std::io::Result<()> {
+        let file_message = self.format(record);
+        self.rotate_if_needed(file_message.len() as u64)?;
+        if self.pending == 0 {
+            // The interval counts from the oldest unflushed record
+            self.last_flush = std::time::Instant::now();
+        }
+        self.file.write_all(file_message.as_bytes())?;
+        self.written += file_message.len() as u64;
+        self.pending += 1;
+
+        if self.flush_due(record.level) {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buffer()
+    }
+
+    fn threshold(&self) -> LogLevel {
+        self.threshold
+    }
+
+    fn max_level(&self) -> Option<LogLevel> {
+        self.max_level
+    }
+}
@@ -0,0 +1,125 @@
+use crate::registry::target_logger;
+use crate::{max_enabled_level, FieldValue, LogLevel, LOGGER, LOG_MODULE};
+use std::fmt::Write as _;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+// ---------- tracing layer ----------
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::TRACE => LogLevel::Verbose,
+            Level::DEBUG => LogLevel::Debug,
+            Level::INFO => LogLevel::Info,
+            Level::WARN => LogLevel::Warning,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+/// `tracing_subscriber::Layer` that renders events through the global `LOGGER`,
+/// or the named logger registered for the module tag or event target.
+/// The current span path (`outer:inner`) is used as the module column.
+pub struct UloggerLayer;
+
+impl<S> Layer<S> for UloggerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let level = event.metadata().level().into();
+
+        // The ulogger module tag wins; fall back to the event target
+        let tag = LOG_MODULE.with(|m| *m.borrow());
+        let name = if tag.is_empty() {
+            event.metadata().target()
+        } else {
+            tag
+        };
+
+        let handle = target_logger(name);
+        let mut logger = match &handle {
+            Some(handle) if level >= handle.max_enabled_level() => handle.lock(),
+            Some(_) => return,
+            None if level >= max_enabled_level() => {
+                let logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+                if !logger.enabled(level) {
+                    return;
+                }
+                logger
+            }
+            None => return,
+        };
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| span.name())
+                    .collect::<Vec<_>>()
+                    .join(":")
+            })
+            .unwrap_or_default();
+        let module = if spans.is_empty() { name } else { &spans };
+
+        logger.set_level(level);
+        if let (Some(file), Some(line)) = (event.metadata().file(), event.metadata().line()) {
+            logger.set_location(file, line);
+        }
+        logger.append_args(format_args!("{}", visitor.message));
+        for (key, value) in visitor.fields {
+            logger.append_kv(key, value);
+        }
+        match &handle {
+            Some(handle) => handle.print_with_module(&mut logger, module),
+            None => logger.print_with_module(module),
+        }
+    }
+}
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl Visit for EventVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.push((field.name(), value.into()));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.push((field.name(), value.into()));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.push((field.name(), value.into()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.push((field.name(), value.into()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.fields.push((field.name(), value.into()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            self.fields
+                .push((field.name(), format!("{:?}", value).into()));
+        }
+    }
+}